@@ -1,18 +1,25 @@
-use clap::{Args, Parser, Subcommand};
+use chrono::{DateTime, NaiveDate};
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use derive_getters::Getters;
-use ethers::signers::WalletError;
+use ethers::signers::{LocalWallet, WalletError};
+use ethers::utils::hex;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
 use tracing::info;
+use tracing::subscriber::SetGlobalDefaultError;
+use tracing_subscriber::EnvFilter;
 
 use graphcast_sdk::{
     build_wallet,
-    graphcast_agent::{
-        message_typing::IdentityValidation, GraphcastAgentConfig, GraphcastAgentError,
-    },
+    graphcast_agent::{message_typing::IdentityValidation, GraphcastAgentConfig},
     graphql::QueryError,
-    init_tracing, wallet_address, GraphcastNetworkName, LogFormat,
+    wallet_address, GraphcastNetworkName, LogFormat,
 };
 
+use crate::messages::domain::MessageDomain;
+use crate::query::AllocationStatus;
+
 #[derive(Clone, Debug, Parser, Serialize, Deserialize, Getters)]
 #[clap(
     name = "graphcast-cli",
@@ -36,25 +43,126 @@ pub struct Config {
         default_value = "5"
     )]
     pub max_retry: u64,
+    #[clap(
+        long,
+        value_name = "RETRY_DELAY",
+        value_parser = Config::parse_duration,
+        env = "RETRY_DELAY",
+        help = "Initial delay before retrying, doubled on every attempt (e.g. 1s)",
+        default_value = "1s"
+    )]
+    pub retry_delay: Duration,
+    #[clap(
+        long,
+        value_name = "MAX_RETRY_DELAY",
+        value_parser = Config::parse_duration,
+        env = "MAX_RETRY_DELAY",
+        help = "Upper bound of the delay between retries (e.g. 1m)",
+        default_value = "1m"
+    )]
+    pub max_retry_delay: Duration,
     #[arg(
         short,
+        long,
         value_name = "config_file",
         env = "CONFIG_FILE",
         help = "Configuration file (toml or yaml format)"
     )]
     pub config_file: Option<String>,
+    #[clap(
+        long,
+        value_name = "STATE_FILE",
+        env = "STATE_FILE",
+        help = "JSON file recording sent messages and indexing status snapshots (default: $XDG_DATA_HOME/graphcast-cli/state.json)"
+    )]
+    pub state_file: Option<String>,
 }
 
 impl Config {
     /// Parse config arguments
+    /// Values are layered with the precedence of configuration file < env < CLI flags
     pub fn args() -> Self {
+        // Look up the configuration file path before the full parse, as required
+        // arguments may only be provided by the file
+        let config_file = Config::command()
+            .ignore_errors(true)
+            .get_matches()
+            .get_one::<String>("config_file")
+            .cloned();
+        if let Some(path) = config_file {
+            if let Err(e) = Config::load_config_file(Path::new(&path)) {
+                Config::command().error(ErrorKind::InvalidValue, e).exit();
+            }
+        }
+
         let config = Config::parse();
+        if let Err(e) = config.validate_message_domain() {
+            Config::command()
+                .error(ErrorKind::ArgumentConflict, e)
+                .exit();
+        }
         std::env::set_var("RUST_LOG", config.radio_infrastructure().log_level.clone());
         // Enables tracing under RUST_LOG variable
-        init_tracing(config.radio_infrastructure().log_format.to_string()).expect("Could not set up global default subscriber for logger, check environmental variable `RUST_LOG` or the CLI input `log-level`");
+        init_tracing(&config.radio_infrastructure().log_format.to_string()).expect("Could not set up global default subscriber for logger, check environmental variable `RUST_LOG` or the CLI input `log-level`");
         config
     }
 
+    /// Read a toml or yaml configuration file and expose its values as the
+    /// environment variables of the corresponding arguments, without overriding
+    /// variables already set in the environment
+    pub fn load_config_file(path: &Path) -> Result<(), ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(ConfigError::ReadStr)?;
+        let value: serde_json::Value = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| ConfigError::ValidateInput(format!("Invalid toml: {e}")))?,
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)
+                .map_err(|e| ConfigError::ValidateInput(format!("Invalid yaml: {e}")))?,
+            _ => {
+                return Err(ConfigError::ValidateInput(format!(
+                    "Configuration file must have a toml or yaml extension: {}",
+                    path.display()
+                )))
+            }
+        };
+        // Type check the file and reject unknown keys
+        serde_json::from_value::<ConfigFile>(value.clone()).map_err(ConfigError::GenerateJson)?;
+
+        let command = Config::command();
+        let mut values = vec![];
+        if let serde_json::Value::Object(sections) = value {
+            for (section, section_value) in sections {
+                match section_value {
+                    serde_json::Value::Object(fields) => values.extend(fields),
+                    v => values.push((section, v)),
+                }
+            }
+        }
+        for (key, value) in values {
+            let Some(env) = command
+                .get_arguments()
+                .find(|arg| arg.get_id() == key.as_str())
+                .and_then(|arg| arg.get_env())
+            else {
+                continue;
+            };
+            if std::env::var_os(env).is_some() {
+                continue;
+            }
+            let env_value = match value {
+                serde_json::Value::Null => continue,
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .map(|v| v.as_str().map(String::from).unwrap_or(v.to_string()))
+                    .collect::<Vec<String>>()
+                    .join(","),
+                v => v.to_string(),
+            };
+            std::env::set_var(env, env_value);
+        }
+        Ok(())
+    }
+
     /// Validate that private key as an Eth wallet
     fn parse_key(value: &str) -> Result<String, WalletError> {
         // The wallet can be stored instead of the original private key
@@ -64,6 +172,64 @@ impl Config {
         Ok(String::from(value))
     }
 
+    /// Parse a duration in seconds, with an optional s, m or h unit suffix
+    fn parse_duration(value: &str) -> Result<Duration, String> {
+        let value = value.trim();
+        let (number, unit_secs) = match value.char_indices().last() {
+            Some((i, 's')) => (&value[..i], 1),
+            Some((i, 'm')) => (&value[..i], 60),
+            Some((i, 'h')) => (&value[..i], 3600),
+            _ => (value, 1),
+        };
+        number
+            .parse::<u64>()
+            .map(|n| Duration::from_secs(n * unit_secs))
+            .map_err(|e| format!("Invalid duration {value}: {e}"))
+    }
+
+    /// Parse an upgrade intent given as subgraph_id:new_hash
+    fn parse_intent(value: &str) -> Result<UpgradePresyncArg, String> {
+        match value.trim().split_once(':') {
+            Some((subgraph_id, new_hash)) if !subgraph_id.is_empty() && !new_hash.is_empty() => {
+                Ok(UpgradePresyncArg {
+                    subgraph_id: subgraph_id.to_string(),
+                    new_hash: new_hash.to_string(),
+                    dry_run: false,
+                })
+            }
+            _ => Err(format!(
+                "Invalid upgrade intent {value}, expected subgraph_id:new_hash"
+            )),
+        }
+    }
+
+    /// Parse a date as YYYY-MM-DD at midnight UTC or as RFC 3339, to a unix timestamp
+    fn parse_date(value: &str) -> Result<i64, String> {
+        let value = value.trim();
+        if let Some(time) = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+        {
+            return Ok(time.timestamp());
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| time.timestamp())
+            .map_err(|e| format!("Invalid date {value}, expected YYYY-MM-DD or RFC 3339: {e}"))
+    }
+
+    /// Parse a percentage between 0 and 100, with an optional % suffix
+    fn parse_percentage(value: &str) -> Result<f32, String> {
+        let percentage = value
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f32>()
+            .map_err(|e| format!("Invalid percentage {value}: {e}"))?;
+        if !(0.0..=100.0).contains(&percentage) {
+            return Err(format!("Percentage must be between 0 and 100: {value}"));
+        }
+        Ok(percentage)
+    }
+
     /// Private key takes precedence over mnemonic
     pub fn wallet_input(&self) -> Result<&String, ConfigError> {
         match (
@@ -78,10 +244,53 @@ impl Config {
         }
     }
 
-    pub async fn to_graphcast_agent_config(
+    /// EIP-712 domain to sign and verify messages under. The mainnet domain is
+    /// used on every network unless the network domain is opted into
+    pub fn message_domain(&self) -> MessageDomain {
+        if self.radio_infrastructure().network_message_domain {
+            MessageDomain::for_network(&self.radio_infrastructure().graphcast_network)
+        } else {
+            MessageDomain::MAINNET
+        }
+    }
+
+    /// Identity validation recovers the sender under the mainnet domain, so the
+    /// network domain is only accepted without it
+    pub fn validate_message_domain(&self) -> Result<(), ConfigError> {
+        let infrastructure = self.radio_infrastructure();
+        if infrastructure.network_message_domain
+            && !matches!(infrastructure.id_validation, IdentityValidation::NoCheck)
+        {
+            return Err(ConfigError::ValidateInput(String::from(
+                "--network-message-domain requires --id-validation no-check",
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn to_graphcast_agent_config(&self) -> Result<GraphcastAgentConfig, ConfigError> {
+        let wallet_key = self.wallet_input()?.to_string();
+        self.graphcast_agent_config(wallet_key).await
+    }
+
+    /// Agent configuration for relaying pre-signed messages. The wallet of the
+    /// agent does not sign them, so a random key is used when none is configured
+    pub async fn to_broadcast_agent_config(&self) -> Result<GraphcastAgentConfig, ConfigError> {
+        let wallet_key = match self.wallet_input() {
+            Ok(key) => key.to_string(),
+            Err(_) => hex::encode(
+                LocalWallet::new(&mut rand::thread_rng())
+                    .signer()
+                    .to_bytes(),
+            ),
+        };
+        self.graphcast_agent_config(wallet_key).await
+    }
+
+    async fn graphcast_agent_config(
         &self,
-    ) -> Result<GraphcastAgentConfig, GraphcastAgentError> {
-        let wallet_key = self.wallet_input().unwrap().to_string();
+        wallet_key: String,
+    ) -> Result<GraphcastAgentConfig, ConfigError> {
         let topics = self.radio_infrastructure().topics.clone();
 
         info!(
@@ -115,11 +324,42 @@ impl Config {
             self.waku().discv5_port,
         )
         .await
+        .map_err(|e| ConfigError::Other(anyhow::anyhow!(e)))
+    }
+}
+
+/// Set up the global subscriber filtered by RUST_LOG. Logs are written to stderr
+/// so that stdout only carries the output of the subcommand, such as json reports
+fn init_tracing(format: &str) -> Result<(), SetGlobalDefaultError> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match format {
+        "json" => tracing::subscriber::set_global_default(builder.json().finish()),
+        "full" => tracing::subscriber::set_global_default(builder.finish()),
+        "compact" => tracing::subscriber::set_global_default(builder.compact().finish()),
+        _ => tracing::subscriber::set_global_default(builder.pretty().finish()),
     }
 }
 
+/// Layout of the configuration file, grouped by the sections of `Config`
+#[allow(unused)]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    graph_stack: Option<GraphStack>,
+    waku: Option<Waku>,
+    radio_infrastructure: Option<RadioInfrastructure>,
+    max_retry: Option<u64>,
+    retry_delay: Option<String>,
+    max_retry_delay: Option<String>,
+    state_file: Option<String>,
+}
+
 #[derive(Clone, Debug, Args, Serialize, Deserialize, Getters, Default)]
 #[group(required = true, multiple = true)]
+#[serde(default, deny_unknown_fields)]
 pub struct GraphStack {
     #[clap(
         long,
@@ -168,6 +408,7 @@ pub struct GraphStack {
 
 #[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
 #[group(required = false, multiple = true)]
+#[serde(default, deny_unknown_fields)]
 pub struct RadioInfrastructure {
     #[clap(
         long,
@@ -177,6 +418,12 @@ pub struct RadioInfrastructure {
         help = "Supported Graphcast networks: mainnet, testnet"
     )]
     pub graphcast_network: GraphcastNetworkName,
+    #[clap(
+        long,
+        env = "NETWORK_MESSAGE_DOMAIN",
+        help = "Sign and verify messages under the EIP-712 domain of the Graphcast network instead of the mainnet domain expected by deployed radios (requires --id-validation no-check)"
+    )]
+    pub network_message_domain: bool,
     #[clap(
         long,
         value_name = "[TOPIC]",
@@ -271,6 +518,7 @@ pub struct RadioInfrastructure {
 
 #[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
 #[group(required = false, multiple = true)]
+#[serde(default, deny_unknown_fields)]
 pub struct Waku {
     #[clap(
         long,
@@ -305,6 +553,7 @@ pub struct Waku {
     #[clap(
         long,
         value_name = "NODE_ADDRESSES",
+        value_delimiter = ',',
         help = "Comma separated static list of waku boot nodes to connect to",
         env = "BOOT_NODE_ADDRESSES"
     )]
@@ -319,6 +568,7 @@ pub struct Waku {
     #[clap(
         long,
         value_name = "DISCV5_ENRS",
+        value_delimiter = ',',
         help = "Comma separated ENRs for Waku discv5 bootstrapping",
         env = "DISCV5_ENRS"
     )]
@@ -344,6 +594,52 @@ pub enum Commands {
     long_about = "A subgraph developer quickly query the indexing status of a deployment (new_hash) at public status APIs of indexers actively allocated to the subgraph_id 
     ")]
     IndexingStatus(IndexingStatusArg),
+    #[clap(
+        about = "Listen to Graphcast messages on content topics and print them to stdout",
+        long_about = "Keep a Graphcast agent running on the configured topics and deployments, verify message senders, and print messages as JSON lines
+    "
+    )]
+    Listen(ListenArg),
+    #[clap(
+        about = "Re-broadcast upgrade intents until their new hash is published",
+        long_about = "Keep a Graphcast agent running and re-broadcast UpgradeIntent messages on a schedule, so that indexers coming online after the first gossip receive them. Each intent stops once the network subgraph shows its new hash as the current deployment of the subgraph
+    "
+    )]
+    Daemon(DaemonArg),
+    #[clap(
+        about = "Print the sent messages and indexing status snapshots recorded in the state file",
+        long_about = "Audit who announced what and how the sync of new deployments progressed, from the messages and indexing status snapshots recorded locally
+    "
+    )]
+    History(HistoryArg),
+    #[clap(
+        about = "Send a DeprecationNotice message for a subgraph or one of its deployments",
+        long_about = "A subgraph owner can announce the date at which a subgraph or a deployment will be deprecated, so that indexers can plan to close their allocations
+    "
+    )]
+    DeprecationNotice(DeprecationNoticeArg),
+    #[clap(
+        about = "Send a RollbackIntent message retracting an upgrade intent",
+        long_about = "A subgraph developer can retract a previously gossiped upgrade intent, e.g. for a broken build, so that indexers stop offchain syncing the abandoned deployment
+    "
+    )]
+    UpgradeCancel(UpgradeCancelArg),
+    #[clap(
+        about = "Print a sent message and the indexing status snapshots of its new hash taken since"
+    )]
+    Show(ShowArg),
+    #[clap(
+        about = "Sign or verify Graphcast message envelopes offline",
+        long_about = "Sign an UpgradeIntent message to a file on a machine holding the key, so that it can be broadcast elsewhere, and verify the signer of a message file or a payload captured from Waku
+    "
+    )]
+    Message(MessageArg),
+    #[clap(
+        about = "Broadcast a pre-signed message envelope",
+        long_about = "Publish a message envelope signed with message sign on the content topic of its identifier, without the private key of the signer on the broadcasting host
+    "
+    )]
+    Broadcast(BroadcastArg),
 }
 
 #[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
@@ -363,10 +659,14 @@ pub struct UpgradePresyncArg {
         help = "Subgraph hash for the upgrade version of the subgraph"
     )]
     pub new_hash: String,
+    #[clap(
+        long,
+        env = "DRY_RUN",
+        help = "Build and sign the message and print it without starting Waku or sending it"
+    )]
+    pub dry_run: bool,
 }
 
-//TODO: Add network to support multi-network queries, currently just take the
-//the first chain at indexing status returned by allocated indexers
 #[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
 #[group(required = true, multiple = true)]
 pub struct IndexingStatusArg {
@@ -384,6 +684,333 @@ pub struct IndexingStatusArg {
         help = "Subgraph deployment hash for the upgrade version of the subgraph"
     )]
     pub new_hash: String,
+    #[clap(
+        long,
+        value_name = "NETWORK",
+        env = "NETWORK",
+        help = "Only report the progress of chains on this network (all chains indexed by the deployment if not provided)"
+    )]
+    pub network: Option<String>,
+    #[clap(
+        long,
+        value_name = "[ALLOCATION_STATUS]",
+        value_enum,
+        value_delimiter = ',',
+        env = "ALLOCATION_STATUS",
+        default_value = "active",
+        help = "Comma separated statuses of allocations whose indexers are queried: active, closed, finalized, claimed"
+    )]
+    pub allocation_status: Vec<AllocationStatus>,
+    #[clap(
+        long,
+        env = "INCLUDE_NEW_HASH_ALLOCATIONS",
+        help = "Also query indexers allocated to the new deployment"
+    )]
+    pub include_new_hash_allocations: bool,
+    #[clap(
+        long,
+        env = "POI_CHECK",
+        help = "Cross-check the proofs of indexing of the new deployment between indexers at a common block"
+    )]
+    pub poi_check: bool,
+    #[clap(
+        long,
+        env = "WATCH",
+        help = "Keep polling the indexing statuses until the sync threshold is reached"
+    )]
+    pub watch: bool,
+    #[clap(
+        long,
+        value_name = "INTERVAL",
+        value_parser = Config::parse_duration,
+        env = "WATCH_INTERVAL",
+        default_value = "60s",
+        help = "Time between polls in watch mode (e.g. 30s, 5m)"
+    )]
+    pub interval: Duration,
+    #[clap(
+        long,
+        value_name = "NUM_INDEXERS",
+        env = "UNTIL_SYNCED",
+        help = "Stop watching once this number of indexers are synced"
+    )]
+    pub until_synced: Option<usize>,
+    #[clap(
+        long,
+        value_name = "PERCENTAGE",
+        value_parser = Config::parse_percentage,
+        env = "UNTIL_PROGRESS",
+        help = "Stop watching once the average progress reaches this percentage (e.g. 95%)"
+    )]
+    pub until_progress: Option<f32>,
+    #[clap(
+        long,
+        value_name = "TIMEOUT",
+        value_parser = Config::parse_duration,
+        env = "WATCH_TIMEOUT",
+        help = "Exit with failure if the sync threshold is not reached within this duration (e.g. 2h)"
+    )]
+    pub timeout: Option<Duration>,
+    #[clap(
+        long,
+        value_name = "OUTPUT",
+        value_enum,
+        env = "OUTPUT",
+        help = "Print the per-indexer statuses and summary to stdout: json, csv, table"
+    )]
+    pub output: Option<OutputFormat>,
+    #[clap(
+        long,
+        value_name = "MAX_CONCURRENT_QUERIES",
+        env = "MAX_CONCURRENT_QUERIES",
+        default_value = "32",
+        help = "Maximum number of status endpoint queries in flight"
+    )]
+    pub max_concurrent_queries: usize,
+    #[clap(
+        long,
+        value_name = "CONNECT_TIMEOUT",
+        value_parser = Config::parse_duration,
+        env = "CONNECT_TIMEOUT",
+        default_value = "5s",
+        help = "Timeout for connecting to a status endpoint"
+    )]
+    pub connect_timeout: Duration,
+    #[clap(
+        long,
+        value_name = "QUERY_TIMEOUT",
+        value_parser = Config::parse_duration,
+        env = "QUERY_TIMEOUT",
+        default_value = "10s",
+        help = "Timeout for a status endpoint query, including reading the response"
+    )]
+    pub query_timeout: Duration,
+    #[clap(
+        long,
+        value_name = "QUERY_RETRIES",
+        env = "QUERY_RETRIES",
+        default_value = "2",
+        help = "Number of retries of a status endpoint query on timeouts, connection and server errors"
+    )]
+    pub query_retries: usize,
+}
+
+#[derive(Clone, Debug, ValueEnum, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    #[default]
+    Table,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = false, multiple = true)]
+pub struct ListenArg {
+    #[clap(
+        long,
+        value_name = "[DEPLOYMENT_HASH]",
+        value_delimiter = ',',
+        env = "DEPLOYMENTS",
+        help = "Comma separated deployment hashes to subscribe to, in addition to the configured topics"
+    )]
+    pub deployments: Vec<String>,
+    #[clap(
+        long,
+        value_name = "DURATION",
+        env = "LISTEN_DURATION",
+        help = "Number of seconds to listen for before exiting (listen until interrupted if not provided)"
+    )]
+    pub duration: Option<u64>,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = true, multiple = true)]
+pub struct DaemonArg {
+    #[clap(
+        long,
+        value_name = "[SUBGRAPH_ID:NEW_HASH]",
+        value_delimiter = ',',
+        value_parser = Config::parse_intent,
+        env = "INTENTS",
+        help = "Comma separated upgrade intents to re-broadcast, each as subgraph_id:new_hash"
+    )]
+    pub intents: Vec<UpgradePresyncArg>,
+    #[clap(
+        long,
+        value_name = "REBROADCAST_INTERVAL",
+        value_parser = Config::parse_duration,
+        env = "REBROADCAST_INTERVAL",
+        default_value = "1h",
+        help = "Time between broadcasts of the pending intents (e.g. 30m, 2h)"
+    )]
+    pub rebroadcast_interval: Duration,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = true, multiple = true)]
+pub struct DeprecationNoticeArg {
+    #[clap(
+        long,
+        value_name = "SUBGRAPH_ID",
+        env = "SUBGRAPH_ID",
+        help = "Subgraph id of the subgraph to be deprecated"
+    )]
+    pub subgraph_id: String,
+    #[clap(
+        long,
+        value_name = "DEPLOYMENT",
+        env = "DEPLOYMENT",
+        help = "Only announce the retirement of this deployment hash (the whole subgraph if not provided)"
+    )]
+    pub deployment: Option<String>,
+    #[clap(
+        long,
+        value_name = "DEPRECATION_DATE",
+        value_parser = Config::parse_date,
+        env = "DEPRECATION_DATE",
+        help = "Date of the deprecation, as YYYY-MM-DD (midnight UTC) or RFC 3339"
+    )]
+    pub deprecation_date: i64,
+    #[clap(
+        long,
+        value_name = "REASON",
+        env = "DEPRECATION_REASON",
+        help = "Explanation for indexers included in the message"
+    )]
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = true, multiple = true)]
+pub struct UpgradeCancelArg {
+    #[clap(
+        long,
+        value_name = "SUBGRAPH_ID",
+        env = "SUBGRAPH_ID",
+        help = "Subgraph id of the upgrade intent to retract"
+    )]
+    pub subgraph_id: String,
+    #[clap(
+        long,
+        value_name = "NEW_HASH",
+        env = "NEW_HASH",
+        help = "New deployment hash of the upgrade intent to retract"
+    )]
+    pub new_hash: String,
+    #[clap(
+        long,
+        value_name = "NONCE",
+        env = "INTENT_NONCE",
+        help = "Nonce of the upgrade intent to retract (the latest intent recorded in the state file if not provided)"
+    )]
+    pub nonce: Option<i64>,
+    #[clap(
+        long,
+        value_name = "REASON",
+        env = "CANCEL_REASON",
+        help = "Explanation for indexers included in the message"
+    )]
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize)]
+pub struct MessageArg {
+    #[clap(subcommand)]
+    pub command: MessageCommands,
+}
+
+#[derive(Clone, Debug, Subcommand, Serialize, Deserialize)]
+pub enum MessageCommands {
+    #[clap(about = "Build and sign an UpgradeIntent message envelope and write it to a file")]
+    Sign(MessageSignArg),
+    #[clap(about = "Decode a message envelope and check its signer against its graph account")]
+    Verify(MessageVerifyArg),
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+pub struct MessageSignArg {
+    #[clap(
+        long,
+        value_name = "SUBGRAPH_ID",
+        env = "SUBGRAPH_ID",
+        help = "Subgraph id shared by the old and new deployment"
+    )]
+    pub subgraph_id: String,
+    #[clap(
+        long,
+        value_name = "NEW_HASH",
+        env = "NEW_HASH",
+        help = "Subgraph hash for the upgrade version of the subgraph"
+    )]
+    pub new_hash: String,
+    #[clap(
+        long,
+        value_name = "IDENTIFIER",
+        env = "IDENTIFIER",
+        help = "Current deployment hash of the subgraph used as the message identifier (queried from the network subgraph if not provided, required when signing offline)"
+    )]
+    pub identifier: Option<String>,
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "File to write the protobuf encoded message envelope to"
+    )]
+    pub file: String,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = true, multiple = false)]
+pub struct MessageVerifyArg {
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "File containing a message envelope, protobuf encoded or as hex"
+    )]
+    pub file: Option<String>,
+    #[clap(
+        long,
+        value_name = "PAYLOAD",
+        help = "Hex encoded message envelope, e.g. a Waku message payload"
+    )]
+    pub payload: Option<String>,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+pub struct BroadcastArg {
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "File containing a signed message envelope, protobuf encoded or as hex"
+    )]
+    pub file: String,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+#[group(required = false, multiple = true)]
+pub struct HistoryArg {
+    #[clap(
+        long,
+        value_name = "SUBGRAPH_ID",
+        env = "SUBGRAPH_ID",
+        help = "Only print the records of this subgraph"
+    )]
+    pub subgraph_id: Option<String>,
+    #[clap(
+        long,
+        value_name = "OUTPUT",
+        value_enum,
+        env = "OUTPUT",
+        default_value = "table",
+        help = "Output format: json, csv, table"
+    )]
+    pub output: OutputFormat,
+}
+
+#[derive(Clone, Debug, Args, Serialize, Deserialize, Default)]
+pub struct ShowArg {
+    #[clap(value_name = "MSG_ID", help = "Id of the sent message")]
+    pub msg_id: String,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -394,8 +1021,186 @@ pub enum ConfigError {
     GenerateJson(serde_json::Error),
     #[error("QueryError: {0}")]
     QueryError(QueryError),
-    #[error("Toml file error: {0}")]
+    #[error("Configuration file error: {0}")]
     ReadStr(std::io::Error),
     #[error("Unknown error: {0}")]
     Other(anyhow::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Loading a configuration file sets process-wide environment variables
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    const ENV_VARS: [&str; 6] = [
+        "GRAPH_ACCOUNT",
+        "GRAPHCAST_NETWORK",
+        "LOG_FORMAT",
+        "ID_VALIDATION",
+        "RADIO_NAME",
+        "MAX_RETRY",
+    ];
+
+    const GRAPH_ACCOUNT: &str = "0xe9a1cabd57700b17945fd81feefba82340d9568f";
+
+    fn clear_env() {
+        for var in ENV_VARS {
+            std::env::remove_var(var);
+        }
+    }
+
+    fn write_config(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("graphcast-cli-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn parse(args: &[&str]) -> Config {
+        let mut argv = vec!["graphcast-cli"];
+        argv.extend_from_slice(args);
+        argv.push("history");
+        Config::try_parse_from(argv).unwrap()
+    }
+
+    fn assert_file_values(config: &Config) {
+        assert_eq!(config.graph_stack().graph_account, GRAPH_ACCOUNT);
+        assert_eq!(config.max_retry, 3);
+        assert!(matches!(
+            config.radio_infrastructure().graphcast_network,
+            GraphcastNetworkName::Mainnet
+        ));
+        assert!(matches!(
+            config.radio_infrastructure().log_format,
+            LogFormat::Json
+        ));
+        assert!(matches!(
+            config.radio_infrastructure().id_validation,
+            IdentityValidation::RegisteredIndexer
+        ));
+    }
+
+    #[test]
+    fn load_toml_config_file() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+        let path = write_config(
+            "config.toml",
+            &format!(
+                r#"
+max_retry = 3
+
+[graph_stack]
+graph_account = "{GRAPH_ACCOUNT}"
+
+[radio_infrastructure]
+graphcast_network = "mainnet"
+log_format = "json"
+id_validation = "registered-indexer"
+radio_name = "from-file"
+"#
+            ),
+        );
+        std::env::set_var("RADIO_NAME", "from-env");
+
+        Config::load_config_file(&path).unwrap();
+        let config = parse(&[]);
+        clear_env();
+
+        assert_file_values(&config);
+        assert_eq!(config.radio_infrastructure().radio_name, "from-env");
+    }
+
+    #[test]
+    fn load_yaml_config_file() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+        let path = write_config(
+            "config.yaml",
+            &format!(
+                r#"
+max_retry: 3
+graph_stack:
+  graph_account: "{GRAPH_ACCOUNT}"
+radio_infrastructure:
+  graphcast_network: mainnet
+  log_format: json
+  id_validation: registered-indexer
+  radio_name: from-file
+"#
+            ),
+        );
+        std::env::set_var("RADIO_NAME", "from-env");
+
+        Config::load_config_file(&path).unwrap();
+        let env_config = parse(&[]);
+        let flag_config = parse(&["--radio-name", "from-flag"]);
+        clear_env();
+
+        assert_file_values(&env_config);
+        assert_eq!(env_config.radio_infrastructure().radio_name, "from-env");
+        assert_eq!(flag_config.radio_infrastructure().radio_name, "from-flag");
+    }
+
+    #[test]
+    fn reject_unknown_config_keys() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+        let path = write_config(
+            "unknown.toml",
+            &format!(
+                r#"
+[graph_stack]
+graph_account = "{GRAPH_ACCOUNT}"
+unknown_key = "value"
+"#
+            ),
+        );
+
+        let result = Config::load_config_file(&path);
+        clear_env();
+
+        assert!(matches!(result, Err(ConfigError::GenerateJson(_))));
+        assert!(std::env::var_os("GRAPH_ACCOUNT").is_none());
+    }
+
+    #[test]
+    fn network_message_domain_requires_no_check() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+
+        let config = parse(&["--network-message-domain"]);
+        assert!(matches!(
+            config.validate_message_domain(),
+            Err(ConfigError::ValidateInput(_))
+        ));
+
+        let config = parse(&[
+            "--network-message-domain",
+            "--id-validation",
+            "no-check",
+            "--graphcast-network",
+            "mainnet",
+        ]);
+        assert!(config.validate_message_domain().is_ok());
+        assert_eq!(config.message_domain(), MessageDomain::MAINNET);
+
+        let config = parse(&[
+            "--network-message-domain",
+            "--id-validation",
+            "no-check",
+            "--graphcast-network",
+            "testnet",
+        ]);
+        assert_eq!(config.message_domain(), MessageDomain::TESTNET);
+
+        let config = parse(&["--graphcast-network", "testnet"]);
+        assert!(config.validate_message_domain().is_ok());
+        assert_eq!(config.message_domain(), MessageDomain::MAINNET);
+    }
+}
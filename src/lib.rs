@@ -1,4 +1,8 @@
 pub mod config;
 pub mod messages;
+pub mod notifier;
 pub mod operator;
 pub mod query;
+pub mod store;
+#[cfg(test)]
+mod stub_server;
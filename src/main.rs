@@ -2,10 +2,19 @@ use std::sync::mpsc;
 
 use dotenv::dotenv;
 use graphcast_cli::{
-    config::{Commands, Config},
-    operator::{operation::indexing_status, RadioOperator},
+    config::{Commands, Config, MessageCommands},
+    operator::{
+        operation::{history, indexing_status, show, upgrade_presync_dry_run},
+        rollback::intent_nonce,
+        signing::{message_sign, message_verify},
+        validation::{
+            validate_deprecation_notice, validate_upgrade_cancel, validate_upgrade_presync,
+        },
+        OperationError, RadioOperator,
+    },
 };
 use graphcast_sdk::{graphcast_agent::GraphcastAgent, WakuMessage};
+use tracing::error;
 
 #[tokio::main]
 async fn main() {
@@ -13,23 +22,99 @@ async fn main() {
     // Parse basic configurations
     let radio_config = Config::args();
 
+    if let Err(e) = run(&radio_config).await {
+        error!(err = e.to_string(), "Failed to run the subcommand");
+        std::process::exit(e.exit_code());
+    }
+}
+
+async fn run(radio_config: &Config) -> Result<(), OperationError> {
     match &radio_config.subcommand() {
+        Commands::UpgradePresync(args) if args.dry_run => {
+            validate_upgrade_presync(radio_config, args).await?;
+            // No Waku node is started for a dry run
+            upgrade_presync_dry_run(radio_config, args).await?;
+        }
         Commands::UpgradePresync(args) => {
+            // Validate before spending time on starting the Waku node
+            validate_upgrade_presync(radio_config, args).await?;
             // The channel is not used in CLI
             let (sender, _) = mpsc::channel::<WakuMessage>();
-            let agent = GraphcastAgent::new(
-                radio_config.to_graphcast_agent_config().await.unwrap(),
-                sender,
-            )
-            .await
-            .expect("Initialize Graphcast agent");
+            let agent =
+                GraphcastAgent::new(radio_config.to_graphcast_agent_config().await?, sender)
+                    .await?;
+
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.upgrade_presync(args).await?;
+        }
+        Commands::Listen(args) => {
+            let (sender, receiver) = mpsc::channel::<WakuMessage>();
+            let agent =
+                GraphcastAgent::new(radio_config.to_graphcast_agent_config().await?, sender)
+                    .await?;
 
-            let radio_operator = RadioOperator::new(&radio_config, agent).await;
-            radio_operator.upgrade_presync(args).await;
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.listen(receiver, args).await;
+        }
+        Commands::Daemon(args) => {
+            for intent in &args.intents {
+                validate_upgrade_presync(radio_config, intent).await?;
+            }
+            // Received rollbacks stop the broadcast of their intents
+            let (sender, receiver) = mpsc::channel::<WakuMessage>();
+            let agent =
+                GraphcastAgent::new(radio_config.to_graphcast_agent_config().await?, sender)
+                    .await?;
+
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.daemon(receiver, args).await?;
+        }
+        Commands::DeprecationNotice(args) => {
+            // Validate before spending time on starting the Waku node
+            validate_deprecation_notice(radio_config, args).await?;
+            // The channel is not used in CLI
+            let (sender, _) = mpsc::channel::<WakuMessage>();
+            let agent =
+                GraphcastAgent::new(radio_config.to_graphcast_agent_config().await?, sender)
+                    .await?;
+
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.deprecation_notice(args).await?;
+        }
+        Commands::UpgradeCancel(args) => {
+            // Validate before spending time on starting the Waku node
+            validate_upgrade_cancel(radio_config, args).await?;
+            let intent_nonce = intent_nonce(radio_config, args)?;
+            // The channel is not used in CLI
+            let (sender, _) = mpsc::channel::<WakuMessage>();
+            let agent =
+                GraphcastAgent::new(radio_config.to_graphcast_agent_config().await?, sender)
+                    .await?;
+
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.upgrade_cancel(args, intent_nonce).await?;
         }
         Commands::IndexingStatus(args) => {
             // No graphcast agent or radio operator needed
-            indexing_status(&radio_config, args).await;
+            indexing_status(radio_config, args).await?;
+        }
+        Commands::Message(args) => match &args.command {
+            // No Waku node is started for offline signing and verification
+            MessageCommands::Sign(args) => message_sign(radio_config, args).await?,
+            MessageCommands::Verify(args) => message_verify(radio_config, args).await?,
+        },
+        Commands::Broadcast(args) => {
+            // The channel is not used in CLI
+            let (sender, _) = mpsc::channel::<WakuMessage>();
+            let agent =
+                GraphcastAgent::new(radio_config.to_broadcast_agent_config().await?, sender)
+                    .await?;
+
+            let radio_operator = RadioOperator::new(radio_config, agent).await;
+            radio_operator.broadcast(args).await?;
         }
+        Commands::History(args) => history(radio_config, args)?,
+        Commands::Show(args) => show(radio_config, args)?,
     };
+    Ok(())
 }
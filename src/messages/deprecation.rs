@@ -0,0 +1,72 @@
+use async_graphql::SimpleObject;
+use ethers_contract::EthAbiType;
+use ethers_core::types::transaction::eip712::Eip712;
+use ethers_derive_eip712::*;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+#[derive(Eip712, EthAbiType, Clone, Message, Serialize, Deserialize, PartialEq, SimpleObject)]
+#[eip712(
+    name = "DeprecationNoticeMessage",
+    version = "0",
+    chain_id = 1,
+    verifying_contract = "0xc944e90c64b2c07662a292be6244bdf05cda44a7"
+)]
+pub struct DeprecationNoticeMessage {
+    /// subgraph id of the subgraph to be deprecated
+    #[prost(string, tag = "1")]
+    pub subgraph_id: String,
+    /// deployment hash to be retired, empty when the whole subgraph is deprecated
+    #[prost(string, tag = "2")]
+    pub deployment: String,
+    /// unix timestamp at which the subgraph or deployment is deprecated
+    #[prost(int64, tag = "3")]
+    pub deprecation_time: i64,
+    /// nonce cached to check against the next incoming message
+    #[prost(int64, tag = "4")]
+    pub nonce: i64,
+    /// Graph account sender - expect the sender to be subgraph owner
+    #[prost(string, tag = "5")]
+    pub graph_account: String,
+    /// optional explanation for indexers
+    #[prost(string, tag = "6")]
+    pub reason: String,
+}
+
+impl DeprecationNoticeMessage {
+    pub fn new(
+        subgraph_id: String,
+        deployment: String,
+        deprecation_time: i64,
+        nonce: i64,
+        graph_account: String,
+        reason: String,
+    ) -> Self {
+        DeprecationNoticeMessage {
+            subgraph_id,
+            deployment,
+            deprecation_time,
+            nonce,
+            graph_account,
+            reason,
+        }
+    }
+
+    pub fn build(
+        subgraph_id: String,
+        deployment: Option<String>,
+        deprecation_time: i64,
+        timestamp: i64,
+        graph_account: String,
+        reason: Option<String>,
+    ) -> Self {
+        DeprecationNoticeMessage::new(
+            subgraph_id,
+            deployment.unwrap_or_default(),
+            deprecation_time,
+            timestamp,
+            graph_account,
+            reason.unwrap_or_default(),
+        )
+    }
+}
@@ -0,0 +1,223 @@
+use async_graphql::OutputType;
+use ethers::signers::{LocalWallet, WalletError};
+use ethers_core::types::transaction::eip712::{EIP712Domain, Eip712};
+use ethers_core::types::{Address, Signature, SignatureError, H256, U256};
+use ethers_core::utils::keccak256;
+use graphcast_sdk::graphcast_agent::message_typing::GraphcastMessage;
+use graphcast_sdk::GraphcastNetworkName;
+use prost::Message;
+use serde::Serialize;
+use std::str::FromStr;
+
+/// EIP-712 domain parameters shared by the message types. Messages signed under
+/// one domain do not verify under another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageDomain {
+    pub version: &'static str,
+    pub chain_id: u64,
+    pub verifying_contract: &'static str,
+}
+
+impl MessageDomain {
+    /// Ethereum mainnet and its GRT token. This is the domain of the current wire
+    /// format, expected by deployed radios on every Graphcast network
+    pub const MAINNET: MessageDomain = MessageDomain {
+        version: "0",
+        chain_id: 1,
+        verifying_contract: "0xc944e90c64b2c07662a292be6244bdf05cda44a7",
+    };
+
+    /// Goerli and its GRT token, matching the default testnet subgraphs
+    pub const TESTNET: MessageDomain = MessageDomain {
+        version: "0",
+        chain_id: 5,
+        verifying_contract: "0x5c946740441c12510a167b447b7de565c20b9e3c",
+    };
+
+    pub fn for_network(network: &GraphcastNetworkName) -> Self {
+        match network {
+            GraphcastNetworkName::Mainnet => MessageDomain::MAINNET,
+            GraphcastNetworkName::Testnet => MessageDomain::TESTNET,
+        }
+    }
+
+    /// Domain of the message type with the given name
+    pub fn eip712_domain(&self, name: &str) -> EIP712Domain {
+        EIP712Domain {
+            name: Some(name.to_string()),
+            version: Some(self.version.to_string()),
+            chain_id: Some(U256::from(self.chain_id)),
+            verifying_contract: Some(
+                self.verifying_contract
+                    .parse::<Address>()
+                    .expect("Verifying contract is a valid address"),
+            ),
+            salt: None,
+        }
+    }
+
+    /// EIP-712 hash of the message under this domain, which is what gets signed
+    pub fn eip712_hash<T: Eip712>(&self, payload: &T) -> Result<[u8; 32], SigningError> {
+        // The name of the message type is kept from its own domain
+        let name = payload
+            .domain()
+            .map_err(|e| SigningError::Eip712(e.to_string()))?
+            .name
+            .unwrap_or_default();
+        let struct_hash = payload
+            .struct_hash()
+            .map_err(|e| SigningError::Eip712(e.to_string()))?;
+        let digest_input = [
+            &[0x19, 0x01][..],
+            &self.eip712_domain(&name).separator()[..],
+            &struct_hash[..],
+        ]
+        .concat();
+        Ok(keccak256(digest_input))
+    }
+}
+
+/// Sign a message under the given domain. Under the mainnet domain, this is the
+/// message that `GraphcastMessage::build` signs
+pub fn sign_message<T>(
+    wallet: &LocalWallet,
+    identifier: String,
+    graph_account: String,
+    nonce: i64,
+    payload: T,
+    domain: &MessageDomain,
+) -> Result<GraphcastMessage<T>, SigningError>
+where
+    T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+{
+    let signature = wallet.sign_hash(H256::from(domain.eip712_hash(&payload)?))?;
+    Ok(GraphcastMessage {
+        identifier,
+        nonce,
+        graph_account,
+        payload,
+        signature: signature.to_string(),
+    })
+}
+
+/// Recover the address that signed the message under the given domain
+pub fn recover_signer<T>(
+    message: &GraphcastMessage<T>,
+    domain: &MessageDomain,
+) -> Result<String, SigningError>
+where
+    T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+{
+    let signature = Signature::from_str(&message.signature)?;
+    let address = signature.recover(H256::from(domain.eip712_hash(&message.payload)?))?;
+    Ok(format!("{:#x}", address))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SigningError {
+    #[error("Could not hash the message: {0}")]
+    Eip712(String),
+    #[error("Could not sign the message: {0}")]
+    Wallet(#[from] WalletError),
+    #[error("Invalid signature: {0}")]
+    Signature(#[from] SignatureError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::upgrade::UpgradeIntentMessage;
+    use ethers::signers::Signer;
+    use graphcast_sdk::wallet_address;
+
+    const IDENTIFIER: &str = "QmacQnSgia4iDPWHpeY6aWxesRFdb8o5DKZUx96zZqEWrB";
+    const NONCE: i64 = 1691078400;
+
+    fn upgrade_intent(graph_account: String) -> UpgradeIntentMessage {
+        UpgradeIntentMessage::build(
+            String::from("CnJMdCkW3pr619gsJVtUPAWxspALPdCMw6o7obzYBNp3"),
+            String::from("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x"),
+            NONCE,
+            graph_account,
+        )
+    }
+
+    fn signed_intent(
+        domain: &MessageDomain,
+    ) -> (LocalWallet, GraphcastMessage<UpgradeIntentMessage>) {
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        let graph_account = wallet_address(&wallet);
+        let message = sign_message(
+            &wallet,
+            IDENTIFIER.to_string(),
+            graph_account.clone(),
+            NONCE,
+            upgrade_intent(graph_account),
+            domain,
+        )
+        .unwrap();
+        (wallet, message)
+    }
+
+    #[test]
+    fn testnet_signed_message_does_not_verify_under_mainnet_domain() {
+        let (wallet, message) = signed_intent(&MessageDomain::TESTNET);
+        let signer = format!("{:#x}", wallet.address());
+
+        assert_eq!(
+            recover_signer(&message, &MessageDomain::TESTNET).unwrap(),
+            signer
+        );
+        assert_ne!(
+            recover_signer(&message, &MessageDomain::MAINNET).unwrap(),
+            signer
+        );
+        // Receivers verify under the domain of the message type
+        assert_ne!(
+            message
+                .recover_sender_address()
+                .unwrap()
+                .to_ascii_lowercase(),
+            signer
+        );
+    }
+
+    #[test]
+    fn mainnet_signed_message_verifies_as_before() {
+        let (wallet, message) = signed_intent(&MessageDomain::MAINNET);
+
+        assert_eq!(
+            message
+                .recover_sender_address()
+                .unwrap()
+                .to_ascii_lowercase(),
+            format!("{:#x}", wallet.address())
+        );
+    }
+
+    #[test]
+    fn domain_follows_graphcast_network() {
+        assert_eq!(
+            MessageDomain::for_network(&GraphcastNetworkName::Mainnet),
+            MessageDomain::MAINNET
+        );
+        assert_eq!(
+            MessageDomain::for_network(&GraphcastNetworkName::Testnet),
+            MessageDomain::TESTNET
+        );
+    }
+
+    /// The mainnet domain hashes messages the way the SDK verifies them
+    #[test]
+    fn mainnet_hash_matches_derive() {
+        let message = upgrade_intent(String::from("0xe9a1cabd57700b17945fd81feefba82340d9568f"));
+        assert_eq!(
+            MessageDomain::MAINNET.eip712_hash(&message).unwrap(),
+            message.encode_eip712().unwrap()
+        );
+        assert_ne!(
+            MessageDomain::TESTNET.eip712_hash(&message).unwrap(),
+            message.encode_eip712().unwrap()
+        );
+    }
+}
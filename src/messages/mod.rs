@@ -1 +1,151 @@
+pub mod deprecation;
+pub mod domain;
+pub mod rollback;
 pub mod upgrade;
+
+use graphcast_sdk::graphcast_agent::message_typing::GraphcastMessage;
+
+use crate::messages::deprecation::DeprecationNoticeMessage;
+use crate::messages::rollback::RollbackIntentMessage;
+use crate::messages::upgrade::UpgradeIntentMessage;
+
+/// Graphcast messages decoded from a raw Waku message payload
+pub enum DecodedMessage {
+    UpgradeIntent(GraphcastMessage<UpgradeIntentMessage>),
+    DeprecationNotice(GraphcastMessage<DeprecationNoticeMessage>),
+    RollbackIntent(GraphcastMessage<RollbackIntentMessage>),
+    /// Payloads that do not match any message type known to the CLI
+    Unknown(Vec<u8>),
+}
+
+impl DecodedMessage {
+    /// Attempt to decode the payload as each known message type, fall back to raw bytes.
+    /// Field tags and wire types differ between message types, and required fields are
+    /// checked as protobuf decoding leaves missing fields empty
+    pub fn decode(payload: &[u8]) -> Self {
+        if let Ok(msg) = GraphcastMessage::<UpgradeIntentMessage>::decode(payload) {
+            if !msg.payload.subgraph_id.is_empty() && !msg.payload.new_hash.is_empty() {
+                return DecodedMessage::UpgradeIntent(msg);
+            }
+        }
+        if let Ok(msg) = GraphcastMessage::<DeprecationNoticeMessage>::decode(payload) {
+            if !msg.payload.subgraph_id.is_empty() && msg.payload.deprecation_time > 0 {
+                return DecodedMessage::DeprecationNotice(msg);
+            }
+        }
+        if let Ok(msg) = GraphcastMessage::<RollbackIntentMessage>::decode(payload) {
+            if !msg.payload.subgraph_id.is_empty()
+                && !msg.payload.new_hash.is_empty()
+                && msg.payload.intent_nonce > 0
+            {
+                return DecodedMessage::RollbackIntent(msg);
+            }
+        }
+        DecodedMessage::Unknown(payload.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::OutputType;
+    use ethers::signers::LocalWallet;
+    use ethers_core::types::transaction::eip712::Eip712;
+    use prost::Message;
+    use serde::Serialize;
+
+    const SUBGRAPH_ID: &str = "CnJMdCkW3pr619gsJVtUPAWxspALPdCMw6o7obzYBNp3";
+    const NEW_HASH: &str = "QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x";
+    const IDENTIFIER: &str = "QmacQnSgia4iDPWHpeY6aWxesRFdb8o5DKZUx96zZqEWrB";
+    const GRAPH_ACCOUNT: &str = "0xe9a1cabd57700b17945fd81feefba82340d9568f";
+    const NONCE: i64 = 1691078400;
+
+    async fn encode<T>(payload: T) -> Vec<u8>
+    where
+        T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+    {
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        GraphcastMessage::build(
+            &wallet,
+            IDENTIFIER.to_string(),
+            GRAPH_ACCOUNT.to_string(),
+            NONCE,
+            payload,
+        )
+        .await
+        .unwrap()
+        .encode_to_vec()
+    }
+
+    #[tokio::test]
+    async fn decode_upgrade_intent() {
+        let payload = encode(UpgradeIntentMessage::build(
+            SUBGRAPH_ID.to_string(),
+            NEW_HASH.to_string(),
+            NONCE,
+            GRAPH_ACCOUNT.to_string(),
+        ))
+        .await;
+
+        match DecodedMessage::decode(&payload) {
+            DecodedMessage::UpgradeIntent(m) => {
+                assert_eq!(m.identifier, IDENTIFIER);
+                assert_eq!(m.payload.new_hash, NEW_HASH);
+                assert_eq!(m.payload.graph_account, GRAPH_ACCOUNT);
+            }
+            _ => panic!("Expected an upgrade intent"),
+        }
+    }
+
+    #[tokio::test]
+    async fn decode_deprecation_notice() {
+        let payload = encode(DeprecationNoticeMessage::build(
+            SUBGRAPH_ID.to_string(),
+            Some(NEW_HASH.to_string()),
+            NONCE + 86400,
+            NONCE,
+            GRAPH_ACCOUNT.to_string(),
+            Some(String::from("Moved to a new subgraph")),
+        ))
+        .await;
+
+        match DecodedMessage::decode(&payload) {
+            DecodedMessage::DeprecationNotice(m) => {
+                assert_eq!(m.payload.deployment, NEW_HASH);
+                assert_eq!(m.payload.deprecation_time, NONCE + 86400);
+                assert_eq!(m.payload.nonce, NONCE);
+            }
+            _ => panic!("Expected a deprecation notice"),
+        }
+    }
+
+    #[tokio::test]
+    async fn decode_rollback_intent() {
+        let payload = encode(RollbackIntentMessage::build(
+            SUBGRAPH_ID.to_string(),
+            NEW_HASH.to_string(),
+            NONCE - 3600,
+            NONCE,
+            GRAPH_ACCOUNT.to_string(),
+            None,
+        ))
+        .await;
+
+        match DecodedMessage::decode(&payload) {
+            DecodedMessage::RollbackIntent(m) => {
+                assert_eq!(m.payload.new_hash, NEW_HASH);
+                assert_eq!(m.payload.intent_nonce, NONCE - 3600);
+                assert_eq!(m.payload.graph_account, GRAPH_ACCOUNT);
+            }
+            _ => panic!("Expected a rollback intent"),
+        }
+    }
+
+    #[test]
+    fn decode_unknown_payload() {
+        assert!(matches!(
+            DecodedMessage::decode(b"not a graphcast message"),
+            DecodedMessage::Unknown(_)
+        ));
+    }
+}
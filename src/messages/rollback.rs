@@ -0,0 +1,76 @@
+use async_graphql::SimpleObject;
+use ethers_contract::EthAbiType;
+use ethers_core::types::transaction::eip712::Eip712;
+use ethers_derive_eip712::*;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// Retracts a previously gossiped upgrade intent. Tag 4 holds an int64 where the
+/// upgrade intent holds a string, so decoding it as an upgrade intent fails. Tag 3
+/// is left unused, so decoding it as a deprecation notice leaves the deprecation
+/// time empty, which `DecodedMessage::decode` rejects
+#[derive(Eip712, EthAbiType, Clone, Message, Serialize, Deserialize, PartialEq, SimpleObject)]
+#[eip712(
+    name = "RollbackIntentMessage",
+    version = "0",
+    chain_id = 1,
+    verifying_contract = "0xc944e90c64b2c07662a292be6244bdf05cda44a7"
+)]
+pub struct RollbackIntentMessage {
+    /// subgraph id of the retracted upgrade intent
+    #[prost(string, tag = "1")]
+    pub subgraph_id: String,
+    /// new deployment hash of the retracted upgrade intent
+    #[prost(string, tag = "2")]
+    pub new_hash: String,
+    /// nonce cached to check against the next incoming message
+    #[prost(int64, tag = "4")]
+    pub nonce: i64,
+    /// Graph account sender - expect the sender to be subgraph owner
+    #[prost(string, tag = "5")]
+    pub graph_account: String,
+    /// nonce of the retracted upgrade intent
+    #[prost(int64, tag = "7")]
+    pub intent_nonce: i64,
+    /// optional explanation for indexers
+    #[prost(string, tag = "8")]
+    pub reason: String,
+}
+
+impl RollbackIntentMessage {
+    pub fn new(
+        subgraph_id: String,
+        new_hash: String,
+        nonce: i64,
+        graph_account: String,
+        intent_nonce: i64,
+        reason: String,
+    ) -> Self {
+        RollbackIntentMessage {
+            subgraph_id,
+            new_hash,
+            nonce,
+            graph_account,
+            intent_nonce,
+            reason,
+        }
+    }
+
+    pub fn build(
+        subgraph_id: String,
+        new_hash: String,
+        intent_nonce: i64,
+        timestamp: i64,
+        graph_account: String,
+        reason: Option<String>,
+    ) -> Self {
+        RollbackIntentMessage::new(
+            subgraph_id,
+            new_hash,
+            timestamp,
+            graph_account,
+            intent_nonce,
+            reason.unwrap_or_default(),
+        )
+    }
+}
@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::RadioInfrastructure;
+
+/// A channel that notifications can be posted to
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn send(&self, client: &reqwest::Client, content: &str) -> Result<(), NotifierError>;
+}
+
+/// Bounds on notification requests, so that an unresponsive channel does not hold
+/// up the operation being reported
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts notifications to every configured channel. Failures are logged and
+/// never interrupt the operation being reported
+#[derive(Clone, Default)]
+pub struct Notifier {
+    client: reqwest::Client,
+    channels: Vec<Arc<dyn NotificationChannel>>,
+}
+
+impl Notifier {
+    pub fn new(channels: Vec<Arc<dyn NotificationChannel>>) -> Self {
+        Notifier::with_timeouts(channels, CONNECT_TIMEOUT, REQUEST_TIMEOUT)
+    }
+
+    pub fn with_timeouts(
+        channels: Vec<Arc<dyn NotificationChannel>>,
+        connect_timeout: Duration,
+        request_timeout: Duration,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(request_timeout)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    err = e.to_string(),
+                    "Could not build the notification client, sending without timeouts"
+                );
+                reqwest::Client::new()
+            });
+        Notifier { client, channels }
+    }
+
+    /// Channels are enabled when all of their settings are provided
+    pub fn from_config(config: &RadioInfrastructure) -> Self {
+        let mut channels: Vec<Arc<dyn NotificationChannel>> = vec![];
+        if let (Some(token), Some(channel)) = (&config.slack_token, &config.slack_channel) {
+            channels.push(Arc::new(SlackBot::new(token.clone(), channel.clone())));
+        }
+        if let Some(webhook) = &config.discord_webhook {
+            channels.push(Arc::new(DiscordBot::new(webhook.clone())));
+        }
+        if let (Some(token), Some(chat_id)) = (&config.telegram_token, config.telegram_chat_id) {
+            channels.push(Arc::new(TelegramBot::new(token.clone(), chat_id)));
+        }
+        Notifier::new(channels)
+    }
+
+    pub async fn notify(&self, content: String) {
+        for channel in &self.channels {
+            match channel.send(&self.client, &content).await {
+                Ok(()) => debug!(channel = channel.name(), "Sent notification"),
+                Err(e) => warn!(
+                    channel = channel.name(),
+                    err = e.to_string(),
+                    "Failed to send notification"
+                ),
+            }
+        }
+    }
+}
+
+pub struct SlackBot {
+    api_url: String,
+    token: String,
+    channel: String,
+}
+
+impl SlackBot {
+    pub fn new(token: String, channel: String) -> Self {
+        SlackBot::with_api_url(String::from("https://slack.com/api"), token, channel)
+    }
+
+    pub fn with_api_url(api_url: String, token: String, channel: String) -> Self {
+        SlackBot {
+            api_url,
+            token,
+            channel,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackBot {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, client: &reqwest::Client, content: &str) -> Result<(), NotifierError> {
+        let response: serde_json::Value = client
+            .post(format!("{}/chat.postMessage", self.api_url))
+            .bearer_auth(&self.token)
+            .json(&json!({ "channel": self.channel, "text": content }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        // Slack reports failures in the body of successful responses
+        if response["ok"].as_bool() != Some(true) {
+            return Err(NotifierError::Rejected(response["error"].to_string()));
+        }
+        Ok(())
+    }
+}
+
+pub struct DiscordBot {
+    webhook: String,
+}
+
+impl DiscordBot {
+    pub fn new(webhook: String) -> Self {
+        DiscordBot { webhook }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordBot {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    async fn send(&self, client: &reqwest::Client, content: &str) -> Result<(), NotifierError> {
+        client
+            .post(&self.webhook)
+            .json(&json!({ "content": content }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct TelegramBot {
+    api_url: String,
+    token: String,
+    chat_id: i64,
+}
+
+impl TelegramBot {
+    pub fn new(token: String, chat_id: i64) -> Self {
+        TelegramBot::with_api_url(String::from("https://api.telegram.org"), token, chat_id)
+    }
+
+    pub fn with_api_url(api_url: String, token: String, chat_id: i64) -> Self {
+        TelegramBot {
+            api_url,
+            token,
+            chat_id,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramBot {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn send(&self, client: &reqwest::Client, content: &str) -> Result<(), NotifierError> {
+        client
+            .post(format!("{}/bot{}/sendMessage", self.api_url, self.token))
+            .json(&json!({ "chat_id": self.chat_id, "text": content }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifierError {
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Notification rejected: {0}")]
+    Rejected(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::stub_server;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Log output captured by a scoped subscriber
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn slack_posts_message() {
+        let (url, server) = stub_server(200, r#"{"ok":true}"#).await;
+        let bot = SlackBot::with_api_url(url, String::from("token"), String::from("upgrades"));
+
+        bot.send(&reqwest::Client::new(), "hello").await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/chat.postMessage");
+        assert!(request
+            .head
+            .lines()
+            .any(|line| line.eq_ignore_ascii_case("authorization: Bearer token")));
+        assert_eq!(
+            request.body,
+            json!({ "channel": "upgrades", "text": "hello" })
+        );
+    }
+
+    #[tokio::test]
+    async fn slack_rejection_is_an_error() {
+        let (url, server) = stub_server(200, r#"{"ok":false,"error":"channel_not_found"}"#).await;
+        let bot = SlackBot::with_api_url(url, String::from("token"), String::from("upgrades"));
+
+        let result = bot.send(&reqwest::Client::new(), "hello").await;
+
+        server.await.unwrap();
+        assert!(
+            matches!(result, Err(NotifierError::Rejected(e)) if e.contains("channel_not_found"))
+        );
+    }
+
+    #[tokio::test]
+    async fn discord_posts_message() {
+        let (url, server) = stub_server(204, "").await;
+        let bot = DiscordBot::new(format!("{}/api/webhooks/1/secret", url));
+
+        bot.send(&reqwest::Client::new(), "hello").await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/api/webhooks/1/secret");
+        assert_eq!(request.body, json!({ "content": "hello" }));
+    }
+
+    #[tokio::test]
+    async fn telegram_posts_message() {
+        let (url, server) = stub_server(200, r#"{"ok":true}"#).await;
+        let bot = TelegramBot::with_api_url(url, String::from("123:secret"), 42);
+
+        bot.send(&reqwest::Client::new(), "hello").await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/bot123:secret/sendMessage");
+        assert_eq!(request.body, json!({ "chat_id": 42, "text": "hello" }));
+    }
+
+    #[tokio::test]
+    async fn failed_notification_is_logged() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let (url, server) = stub_server(500, r#"{"message":"unavailable"}"#).await;
+        let notifier = Notifier::new(vec![Arc::new(DiscordBot::new(url))]);
+
+        notifier.notify(String::from("hello")).await;
+
+        server.await.unwrap();
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("Failed to send notification"));
+        assert!(logs.contains("discord"));
+        assert!(logs.contains("500"));
+    }
+
+    #[tokio::test]
+    async fn unresponsive_channel_times_out() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        // Accept the connection and never respond
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(stream);
+        });
+        let notifier = Notifier::with_timeouts(
+            vec![Arc::new(DiscordBot::new(url))],
+            Duration::from_secs(1),
+            Duration::from_millis(200),
+        );
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            notifier.notify(String::from("hello")),
+        )
+        .await
+        .expect("Notification is bounded by the request timeout");
+
+        server.abort();
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("Failed to send notification"));
+    }
+}
@@ -0,0 +1,95 @@
+use async_graphql::OutputType;
+use chrono::Utc;
+use ethers_core::types::transaction::eip712::Eip712;
+use graphcast_sdk::graphcast_agent::message_typing::GraphcastMessage;
+use prost::Message;
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::BroadcastArg;
+use crate::messages::DecodedMessage;
+use crate::operator::signing::{read_envelope, verify_message};
+use crate::operator::{OperationError, RadioOperator};
+use crate::store::SentMessage;
+
+impl RadioOperator {
+    /// Publish a message envelope signed elsewhere, after validating its sender
+    /// with the configured identity validation
+    pub async fn broadcast(&self, args: &BroadcastArg) -> Result<String, OperationError> {
+        let envelope = read_envelope(Some(&args.file), None)?;
+        match DecodedMessage::decode(&envelope) {
+            DecodedMessage::UpgradeIntent(m) => {
+                self.publish_signed(m, "UpgradeIntentMessage").await
+            }
+            DecodedMessage::DeprecationNotice(m) => {
+                self.publish_signed(m, "DeprecationNoticeMessage").await
+            }
+            DecodedMessage::RollbackIntent(m) => {
+                self.publish_signed(m, "RollbackIntentMessage").await
+            }
+            DecodedMessage::Unknown(_) => Err(OperationError::Message(String::from(
+                "Envelope does not contain a known Graphcast message",
+            ))),
+        }
+    }
+
+    async fn publish_signed<T>(
+        &self,
+        graphcast_message: GraphcastMessage<T>,
+        message_type: &str,
+    ) -> Result<String, OperationError>
+    where
+        T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+    {
+        let verified = verify_message(&self.config, graphcast_message.clone(), message_type).await;
+        if let Some(e) = &verified.sender_error {
+            return Err(OperationError::Message(format!(
+                "Sender {} of graph account {} is not valid: {}",
+                verified.signer.as_deref().unwrap_or("unknown"),
+                verified.graph_account,
+                e
+            )));
+        }
+        let identifier = graphcast_message.identifier.clone();
+        self.graphcast_agent
+            .update_content_topics(vec![identifier.clone()])
+            .await;
+
+        // The envelope is published as is, its signature stays valid
+        let msg_id = self
+            .retry_gossip(|| async { self.publish(&graphcast_message) })
+            .await?;
+        info!(msg_id, message_type, identifier, "Broadcast signed message");
+
+        let field = |name: &str| {
+            verified.payload[name]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        self.store.record_message(SentMessage {
+            msg_id: msg_id.clone(),
+            message_type: String::from(message_type),
+            identifier,
+            subgraph_id: field("subgraph_id"),
+            new_hash: verified.payload["new_hash"]
+                .as_str()
+                .or(verified.payload["deployment"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            nonce: verified.nonce,
+            time: Utc::now().timestamp(),
+            sender: verified.signer.clone().unwrap_or_default(),
+            graph_account: verified.graph_account.clone(),
+        });
+        self.notifier
+            .notify(format!(
+                "Broadcast pre-signed {} for subgraph {}, msg_id: {}",
+                message_type,
+                field("subgraph_id"),
+                msg_id
+            ))
+            .await;
+        Ok(msg_id)
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use chrono::Utc;
+use graphcast_sdk::graphcast_agent::GraphcastAgentError;
+use graphcast_sdk::graphql::client_graph_account::subgraph_hash_by_id;
+use graphcast_sdk::WakuMessage;
+use tokio::time::{sleep, Instant};
+use tracing::{info, warn};
+
+use crate::config::{DaemonArg, UpgradePresyncArg};
+use crate::operator::{OperationError, RadioOperator};
+use crate::query::query_subgraph_info;
+
+/// Upgrade intent waiting for its new hash to be published
+#[derive(Debug, Clone)]
+struct PendingIntent {
+    identifier: String,
+    intent: UpgradePresyncArg,
+    /// Whether a broadcast was sent and notified, re-broadcasts are not notified
+    announced: bool,
+}
+
+impl RadioOperator {
+    /// Re-broadcast the upgrade intents at the rebroadcast interval until the
+    /// network subgraph shows each new hash as the current deployment of its
+    /// subgraph, or the intent is cancelled. Publication is checked and content
+    /// topics are refreshed every topic_update_interval. Cancellations are read
+    /// from the state file and from rollbacks received on the intent topics
+    pub async fn daemon(
+        &self,
+        receiver: Receiver<WakuMessage>,
+        args: &DaemonArg,
+    ) -> Result<(), OperationError> {
+        let mut pending = vec![];
+        for intent in &args.intents {
+            let identifier = subgraph_hash_by_id(
+                self.config.graph_stack().network_subgraph(),
+                &self.config.graph_stack().graph_account,
+                &intent.subgraph_id,
+            )
+            .await
+            .map_err(GraphcastAgentError::from)?;
+            pending.push(PendingIntent {
+                identifier,
+                intent: intent.clone(),
+                announced: false,
+            });
+        }
+        let topic_update_interval =
+            Duration::from_secs(self.config.radio_infrastructure().topic_update_interval);
+        info!(
+            num_intents = pending.len(),
+            rebroadcast_interval = format!("{}s", args.rebroadcast_interval.as_secs()),
+            topic_update_interval = format!("{}s", topic_update_interval.as_secs()),
+            "Starting upgrade intent daemon"
+        );
+
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
+        let started = Utc::now().timestamp();
+        let mut last_broadcast: Option<Instant> = None;
+        let local_sender_id = self.local_sender_id();
+        let mut retracted = HashSet::new();
+        loop {
+            pending = self.unretracted_intents(pending, started);
+            retracted.extend(self.received_retractions(&receiver, &local_sender_id).await);
+            pending.retain(|p| {
+                let key = (p.intent.subgraph_id.clone(), p.intent.new_hash.clone());
+                let is_retracted = retracted.contains(&key);
+                if is_retracted {
+                    info!(
+                        subgraph_id = p.intent.subgraph_id,
+                        new_hash = p.intent.new_hash,
+                        "Received a rollback of the upgrade intent, no longer broadcasting it"
+                    );
+                }
+                !is_retracted
+            });
+            pending = self.unpublished_intents(pending).await;
+            if pending.is_empty() {
+                info!("No pending upgrade intent, stopping the daemon");
+                self.notifier
+                    .notify(String::from(
+                        "No pending upgrade intent, stopped the upgrade intent daemon",
+                    ))
+                    .await;
+                return Ok(());
+            }
+            self.graphcast_agent
+                .update_content_topics(pending.iter().map(|p| p.identifier.clone()).collect())
+                .await;
+
+            if !last_broadcast.is_some_and(|t| t.elapsed() < args.rebroadcast_interval) {
+                for i in 0..pending.len() {
+                    let p = &pending[i];
+                    // Retries back off for a while, so shutdown is not left waiting
+                    let result = tokio::select! {
+                        _ = &mut shutdown => {
+                            self.stop_daemon(&pending).await;
+                            return Ok(());
+                        }
+                        result = self.gossip_with_retry(&p.intent, !p.announced) => result,
+                    };
+                    // A failed broadcast is attempted again at the next interval
+                    match result {
+                        Ok(_) => pending[i].announced = true,
+                        Err(e) => warn!(
+                            subgraph_id = p.intent.subgraph_id,
+                            new_hash = p.intent.new_hash,
+                            err = e.to_string(),
+                            "Failed to re-broadcast upgrade intent"
+                        ),
+                    }
+                }
+                last_broadcast = Some(Instant::now());
+            }
+
+            tokio::select! {
+                _ = &mut shutdown => {
+                    self.stop_daemon(&pending).await;
+                    return Ok(());
+                }
+                _ = sleep(topic_update_interval.min(args.rebroadcast_interval)) => {}
+            }
+        }
+    }
+
+    /// Report the intents still pending when the daemon is interrupted
+    async fn stop_daemon(&self, pending: &[PendingIntent]) {
+        info!(num_intents = pending.len(), "Stopped the daemon");
+        self.notifier
+            .notify(format!(
+                "Stopped the upgrade intent daemon with {} pending intent(s): {}",
+                pending.len(),
+                pending
+                    .iter()
+                    .map(|p| format!("{}:{}", p.intent.subgraph_id, p.intent.new_hash))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))
+            .await;
+    }
+
+    /// Drop the intents retracted with upgrade-cancel since the daemon started, as
+    /// recorded in the state file
+    fn unretracted_intents(&self, intents: Vec<PendingIntent>, since: i64) -> Vec<PendingIntent> {
+        let Ok(state) = self.store.load() else {
+            return intents;
+        };
+        intents
+            .into_iter()
+            .filter(|p| {
+                let retracted = state.sent_messages.iter().any(|m| {
+                    m.message_type == "RollbackIntentMessage"
+                        && m.subgraph_id == p.intent.subgraph_id
+                        && m.new_hash == p.intent.new_hash
+                        && m.time >= since
+                });
+                if retracted {
+                    info!(
+                        subgraph_id = p.intent.subgraph_id,
+                        new_hash = p.intent.new_hash,
+                        "Upgrade intent was cancelled, no longer broadcasting it"
+                    );
+                }
+                !retracted
+            })
+            .collect()
+    }
+
+    /// Subgraph ids and new hashes retracted by valid rollbacks of the configured
+    /// graph account received since the last check, e.g. sent from another host
+    async fn received_retractions(
+        &self,
+        receiver: &Receiver<WakuMessage>,
+        local_sender_id: &str,
+    ) -> Vec<(String, String)> {
+        let graph_account = &self.config.graph_stack().graph_account;
+        let mut retractions = vec![];
+        while let Ok(msg) = receiver.try_recv() {
+            let received = self.process_waku_message(&msg, local_sender_id).await;
+            if received.message_type != "RollbackIntentMessage" || !received.valid_sender {
+                continue;
+            }
+            if !received
+                .graph_account
+                .as_deref()
+                .is_some_and(|account| account.eq_ignore_ascii_case(graph_account))
+            {
+                continue;
+            }
+            let field = |name: &str| {
+                received.payload[name]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            retractions.push((field("subgraph_id"), field("new_hash")));
+        }
+        retractions
+    }
+
+    /// Keep the intents whose new hash is not published yet. Intents are kept
+    /// when the network subgraph cannot be queried
+    async fn unpublished_intents(&self, intents: Vec<PendingIntent>) -> Vec<PendingIntent> {
+        let mut pending = vec![];
+        for p in intents {
+            match query_subgraph_info(
+                self.config.graph_stack().network_subgraph(),
+                &p.intent.subgraph_id,
+            )
+            .await
+            {
+                Ok(info) if info.current_deployment.as_deref() == Some(&p.intent.new_hash) => {
+                    info!(
+                        subgraph_id = p.intent.subgraph_id,
+                        new_hash = p.intent.new_hash,
+                        "New hash is published, no longer broadcasting the upgrade intent"
+                    );
+                    self.notifier
+                        .notify(format!(
+                            "Subgraph {} published {}, no longer broadcasting the upgrade intent",
+                            p.intent.subgraph_id, p.intent.new_hash
+                        ))
+                        .await;
+                }
+                Ok(info) if !info.active => {
+                    warn!(
+                        subgraph_id = p.intent.subgraph_id,
+                        "Subgraph is deprecated, no longer broadcasting the upgrade intent"
+                    );
+                }
+                Ok(_) => pending.push(p),
+                Err(e) => {
+                    warn!(
+                        subgraph_id = p.intent.subgraph_id,
+                        err = e.to_string(),
+                        "Could not check whether the new hash is published"
+                    );
+                    pending.push(p);
+                }
+            }
+        }
+        pending
+    }
+}
@@ -0,0 +1,59 @@
+use graphcast_sdk::graphcast_agent::GraphcastAgentError;
+use graphcast_sdk::graphql::client_graph_account::subgraph_hash_by_id;
+
+use crate::config::DeprecationNoticeArg;
+use crate::messages::deprecation::DeprecationNoticeMessage;
+use crate::operator::{OperationError, RadioOperator};
+
+impl RadioOperator {
+    pub async fn send_deprecation_notice(
+        &self,
+        args: &DeprecationNoticeArg,
+    ) -> Result<String, OperationError> {
+        let graph_account = self.config.graph_stack().graph_account.clone();
+        self.send_and_record(
+            "DeprecationNoticeMessage",
+            &args.subgraph_id,
+            args.deployment.as_deref().unwrap_or_default(),
+            |time| {
+                DeprecationNoticeMessage::build(
+                    args.subgraph_id.clone(),
+                    args.deployment.clone(),
+                    args.deprecation_date,
+                    time,
+                    graph_account,
+                    args.reason.clone(),
+                )
+            },
+            Some(&format!(
+                "for subgraph {}{}",
+                args.subgraph_id,
+                args.deployment
+                    .as_ref()
+                    .map(|deployment| format!(" deployment {}", deployment))
+                    .unwrap_or_default()
+            )),
+        )
+        .await
+    }
+
+    /// Subscribe to the topic of the subgraph and send the deprecation notice
+    pub async fn deprecation_notice(
+        &self,
+        args: &DeprecationNoticeArg,
+    ) -> Result<String, OperationError> {
+        let identifier = subgraph_hash_by_id(
+            self.config.graph_stack().network_subgraph(),
+            &self.config.graph_stack().graph_account,
+            &args.subgraph_id,
+        )
+        .await
+        .map_err(GraphcastAgentError::from)?;
+        self.graphcast_agent
+            .update_content_topics(vec![identifier])
+            .await;
+
+        self.retry_gossip(|| self.send_deprecation_notice(args))
+            .await
+    }
+}
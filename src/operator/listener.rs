@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+use async_graphql::OutputType;
+use ethers::utils::hex;
+use ethers_core::types::transaction::eip712::Eip712;
+use graphcast_sdk::graphcast_agent::message_typing::GraphcastMessage;
+use graphcast_sdk::WakuMessage;
+use prost::Message;
+use serde::Serialize;
+use tokio::time::{sleep, Instant};
+use tracing::{debug, info, warn};
+
+use crate::config::ListenArg;
+use crate::messages::domain::recover_signer;
+use crate::messages::DecodedMessage;
+use crate::operator::RadioOperator;
+
+/// Incoming message as printed to stdout
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceivedMessage {
+    pub content_topic: String,
+    pub message_type: String,
+    pub identifier: Option<String>,
+    pub nonce: Option<i64>,
+    pub graph_account: Option<String>,
+    pub sender: Option<String>,
+    pub valid_sender: bool,
+    pub error: Option<String>,
+    /// Upgrade intent retracted by a rollback received earlier
+    pub cancelled: bool,
+    pub payload: serde_json::Value,
+}
+
+/// Latest upgrade intent nonce retracted by valid rollback messages, keyed by
+/// subgraph id, new hash and graph account so that only the sender of an
+/// intent can retract it
+#[derive(Debug, Default)]
+struct RetractedIntents(HashMap<(String, String, String), i64>);
+
+impl RetractedIntents {
+    /// Record rollbacks and flag the upgrade intents they retract
+    fn apply(&mut self, received: &mut ReceivedMessage) {
+        let field = |name: &str| {
+            received.payload[name]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        let key = (
+            field("subgraph_id"),
+            field("new_hash"),
+            field("graph_account"),
+        );
+        match received.message_type.as_str() {
+            "RollbackIntentMessage" if received.valid_sender => {
+                let intent_nonce = received.payload["intent_nonce"]
+                    .as_i64()
+                    .unwrap_or_default();
+                warn!(
+                    subgraph_id = key.0,
+                    new_hash = key.1,
+                    intent_nonce,
+                    reason = received.payload["reason"].as_str(),
+                    "Upgrade intent retracted, stop offchain syncing the new deployment"
+                );
+                let retracted = self.0.entry(key).or_default();
+                *retracted = (*retracted).max(intent_nonce);
+            }
+            "UpgradeIntentMessage" => {
+                let nonce = received.payload["nonce"].as_i64().unwrap_or_default();
+                received.cancelled = self
+                    .0
+                    .get(&key)
+                    .is_some_and(|retracted| nonce <= *retracted);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl RadioOperator {
+    /// Listen to the subscribed content topics and print every incoming message
+    /// until interrupted or the configured duration elapsed
+    pub async fn listen(&self, receiver: Receiver<WakuMessage>, args: &ListenArg) {
+        let mut topics = self.config.radio_infrastructure().topics.clone();
+        topics.extend(args.deployments.iter().cloned());
+        self.graphcast_agent
+            .update_content_topics(topics.clone())
+            .await;
+        info!(
+            topics = tracing::field::debug(&topics),
+            duration = args.duration,
+            "Listening to Graphcast messages"
+        );
+
+        let deadline = args
+            .duration
+            .map(|secs| Instant::now() + Duration::from_secs(secs));
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
+        let mut retracted_intents = RetractedIntents::default();
+        // Derived once, as deriving the wallet from a mnemonic is slow
+        let local_sender_id = self.local_sender_id();
+
+        loop {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            match receiver.try_recv() {
+                Ok(msg) => {
+                    let mut received = self.process_waku_message(&msg, &local_sender_id).await;
+                    retracted_intents.apply(&mut received);
+                    match serde_json::to_string(&received) {
+                        Ok(line) => println!("{line}"),
+                        Err(e) => warn!(err = e.to_string(), "Failed to serialize message"),
+                    }
+                }
+                Err(TryRecvError::Empty) => {
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = sleep(Duration::from_millis(100)) => {}
+                    }
+                }
+                Err(TryRecvError::Disconnected) => {
+                    warn!("Graphcast agent stopped forwarding messages");
+                    break;
+                }
+            }
+        }
+        info!("Stopped listening");
+    }
+
+    /// Decode a Waku message and validate the sender of known message types
+    pub async fn process_waku_message(
+        &self,
+        msg: &WakuMessage,
+        local_sender_id: &str,
+    ) -> ReceivedMessage {
+        let content_topic = msg.content_topic().to_string();
+        match DecodedMessage::decode(msg.payload()) {
+            DecodedMessage::UpgradeIntent(m) => {
+                self.received_message(content_topic, m, "UpgradeIntentMessage", local_sender_id)
+                    .await
+            }
+            DecodedMessage::DeprecationNotice(m) => {
+                self.received_message(
+                    content_topic,
+                    m,
+                    "DeprecationNoticeMessage",
+                    local_sender_id,
+                )
+                .await
+            }
+            DecodedMessage::RollbackIntent(m) => {
+                self.received_message(content_topic, m, "RollbackIntentMessage", local_sender_id)
+                    .await
+            }
+            DecodedMessage::Unknown(bytes) => {
+                debug!(content_topic, "Received message of unknown type");
+                ReceivedMessage {
+                    content_topic,
+                    message_type: String::from("Unknown"),
+                    identifier: None,
+                    nonce: None,
+                    graph_account: None,
+                    sender: None,
+                    valid_sender: false,
+                    error: None,
+                    cancelled: false,
+                    payload: serde_json::Value::String(hex::encode(bytes)),
+                }
+            }
+        }
+    }
+
+    /// Recover and validate the sender of a decoded message
+    async fn received_message<T>(
+        &self,
+        content_topic: String,
+        graphcast_message: GraphcastMessage<T>,
+        message_type: &str,
+        local_sender_id: &str,
+    ) -> ReceivedMessage
+    where
+        T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+    {
+        let payload = serde_json::to_value(&graphcast_message.payload).unwrap_or_default();
+        let domain = self.config.message_domain();
+        let sender = recover_signer(&graphcast_message, &domain).ok();
+        // The network domain is only accepted with `--id-validation no-check`,
+        // under which the SDK check does not depend on the recovered sender
+        let error = graphcast_message
+            .valid_sender(
+                self.config.graph_stack().registry_subgraph(),
+                self.config.graph_stack().network_subgraph(),
+                local_sender_id.to_string(),
+                &self.config.radio_infrastructure().id_validation,
+            )
+            .await
+            .err()
+            .map(|e| e.to_string());
+
+        ReceivedMessage {
+            content_topic,
+            message_type: String::from(message_type),
+            identifier: Some(graphcast_message.identifier.clone()),
+            nonce: Some(graphcast_message.nonce),
+            graph_account: Some(graphcast_message.graph_account.clone()),
+            sender,
+            valid_sender: error.is_none(),
+            error,
+            cancelled: false,
+            payload,
+        }
+    }
+}
@@ -1,16 +1,34 @@
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::debug;
 
-use graphcast_sdk::graphcast_agent::GraphcastAgent;
+use graphcast_sdk::graphcast_agent::{GraphcastAgent, GraphcastAgentError};
+use graphcast_sdk::{build_wallet, wallet_address};
 
-use crate::config::Config;
+use crate::config::{Config, ConfigError};
+use crate::notifier::Notifier;
+use crate::operator::validation::ValidationError;
+use crate::query::StatusQueryError;
+use crate::store::{Store, StoreError};
+pub mod broadcast;
+pub mod daemon;
+pub mod deprecation;
+pub mod listener;
 pub mod operation;
+pub mod poi;
+pub mod rollback;
+pub mod signing;
+pub mod summary;
+pub mod validation;
+pub mod watch;
 
 /// Radio operator contains all states needed for radio operations
 #[allow(unused)]
 pub struct RadioOperator {
     config: Config,
     graphcast_agent: Arc<GraphcastAgent>,
+    notifier: Notifier,
+    store: Store,
 }
 
 impl RadioOperator {
@@ -20,13 +38,61 @@ impl RadioOperator {
         debug!("Initializing Graphcast Agent");
         let graphcast_agent = Arc::new(agent);
 
+        let notifier = Notifier::from_config(config.radio_infrastructure());
+        let store = Store::from_config(config);
+
         RadioOperator {
             config: config.clone(),
             graphcast_agent,
+            notifier,
+            store,
         }
     }
 
     pub fn graphcast_agent(&self) -> &GraphcastAgent {
         &self.graphcast_agent
     }
+
+    /// Address of the configured wallet, empty when no wallet is configured
+    pub fn local_sender_id(&self) -> String {
+        self.config
+            .wallet_input()
+            .ok()
+            .and_then(|input| build_wallet(input).ok())
+            .map(|wallet| wallet_address(&wallet))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OperationError {
+    #[error("Configuration error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("Graphcast agent error: {0}")]
+    Agent(#[from] GraphcastAgentError),
+    #[error("Query error: {0}")]
+    Query(#[from] StatusQueryError),
+    #[error("Sync threshold not reached within {0:?}")]
+    Timeout(Duration),
+    #[error("Message error: {0}")]
+    Message(String),
+    #[error("Invalid upgrade intent: {0}")]
+    Validation(#[from] ValidationError),
+    #[error("State file error: {0}")]
+    Store(#[from] StoreError),
+}
+
+impl OperationError {
+    /// Process exit code to report the failure category to scripts
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            OperationError::Config(_) => 2,
+            OperationError::Agent(_) => 3,
+            OperationError::Query(_) => 4,
+            OperationError::Timeout(_) => 5,
+            OperationError::Message(_) => 6,
+            OperationError::Validation(_) => 7,
+            OperationError::Store(_) => 8,
+        }
+    }
 }
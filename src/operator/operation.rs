@@ -1,40 +1,128 @@
+use async_graphql::OutputType;
 use chrono::Utc;
-use graphcast_sdk::graphql::client_graph_account::subgraph_hash_by_id;
-use graphcast_sdk::graphql::QueryError;
+use ethers::utils::hex;
+use ethers_core::types::transaction::eip712::Eip712;
+use graphcast_sdk::graphql::{client_graph_account::subgraph_hash_by_id, QueryError};
+use graphcast_sdk::{build_wallet, waku_handling::build_content_topics, wallet_address};
+use prost::Message;
+use rand::Rng;
+use serde::Serialize;
+use serde_json::json;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
-use graphcast_sdk::graphcast_agent::GraphcastAgentError;
+use graphcast_sdk::graphcast_agent::{message_typing::GraphcastMessage, GraphcastAgentError};
 
-use crate::config::{Config, IndexingStatusArg, UpgradePresyncArg};
+use crate::config::{
+    Config, ConfigError, HistoryArg, IndexingStatusArg, ShowArg, UpgradePresyncArg,
+};
+use crate::messages::domain::sign_message;
 use crate::messages::upgrade::UpgradeIntentMessage;
+use crate::notifier::Notifier;
+use crate::operator::poi::poi_check;
+use crate::operator::summary::{
+    current_blocks, AllocatorBehind, ChainSummary, CurrentBlocks, FatalErrorSummary,
+    IndexerStatusRow, StatusReport, StatusSummary, UnreachableSummary,
+};
+use crate::operator::watch::watch_indexing_status;
+use crate::operator::OperationError;
 use crate::operator::RadioOperator;
-use crate::query::{query_indexer_public_api, query_indexing_statuses, IndexerInfo};
+use crate::query::{
+    query_indexer_public_api, query_indexing_statuses, query_subgraph_info, ChainStatus,
+    IndexerInfo, IndexerUrl, StatusClient, StatusQueryError,
+};
+use crate::store::{render_history, show_message, SentMessage, StatusSnapshot, Store};
 
 impl RadioOperator {
+    /// Send the upgrade intent, notifying the configured channels if requested
     pub async fn gossip_one_shot(
         &self,
         args: &UpgradePresyncArg,
-    ) -> Result<String, GraphcastAgentError> {
-        // configure radio config to parse in a subcommand for the radio payload message?
-        let new_hash = args.new_hash.to_string();
-        let subgraph_id = args.subgraph_id.to_string();
+        notify: bool,
+    ) -> Result<String, OperationError> {
+        let graph_account = self.config.graph_stack().graph_account.clone();
+        self.send_and_record(
+            "UpgradeIntentMessage",
+            &args.subgraph_id,
+            &args.new_hash,
+            |time| {
+                UpgradeIntentMessage::build(
+                    args.subgraph_id.clone(),
+                    args.new_hash.clone(),
+                    time,
+                    graph_account,
+                )
+            },
+            notify
+                .then(|| {
+                    format!(
+                        "for subgraph {} with new deployment {}",
+                        args.subgraph_id, args.new_hash
+                    )
+                })
+                .as_deref(),
+        )
+        .await
+    }
+
+    /// Sign a message built with the current time as its nonce under the configured
+    /// domain and publish it on the topic of the subgraph, then record it to the
+    /// state file and notify the summary, if any
+    pub async fn send_and_record<T>(
+        &self,
+        message_type: &str,
+        subgraph_id: &str,
+        hash: &str,
+        build: impl FnOnce(i64) -> T,
+        summary: Option<&str>,
+    ) -> Result<String, OperationError>
+    where
+        T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+    {
         let time = Utc::now().timestamp();
         let graph_account = self.config.graph_stack().graph_account.clone();
         let identifier = subgraph_hash_by_id(
             self.config.graph_stack().network_subgraph(),
             &graph_account,
-            &subgraph_id,
+            subgraph_id,
         )
-        .await?;
-        let radio_message =
-            UpgradeIntentMessage::build(subgraph_id, new_hash.clone(), time, graph_account);
-        match self
-            .graphcast_agent
-            .send_message(&identifier, radio_message, time)
-            .await
-        {
+        .await
+        .map_err(GraphcastAgentError::from)?;
+        let wallet = build_wallet(self.config.wallet_input()?)
+            .map_err(|e| ConfigError::ValidateInput(e.to_string()))?;
+        let graphcast_message = sign_message(
+            &wallet,
+            identifier.clone(),
+            graph_account.clone(),
+            time,
+            build(time),
+            &self.config.message_domain(),
+        )
+        .map_err(|e| OperationError::Message(e.to_string()))?;
+        match self.publish(&graphcast_message) {
             Ok(msg_id) => {
-                info!(msg_id, "Sent message");
+                info!(msg_id, message_type, "Sent message");
+                self.store.record_message(SentMessage {
+                    msg_id: msg_id.clone(),
+                    message_type: message_type.to_string(),
+                    identifier,
+                    subgraph_id: subgraph_id.to_string(),
+                    new_hash: hash.to_string(),
+                    nonce: time,
+                    time,
+                    sender: wallet_address(&wallet),
+                    graph_account,
+                });
+                if let Some(summary) = summary {
+                    self.notifier
+                        .notify(format!(
+                            "Sent {} {}, msg_id: {}",
+                            message_type, summary, msg_id
+                        ))
+                        .await;
+                }
                 Ok(msg_id)
             }
             Err(e) => {
@@ -44,10 +132,36 @@ impl RadioOperator {
         }
     }
 
-    /// radio attempt to send message until success or max at configured retry
-    pub async fn upgrade_presync(&self, args: &UpgradePresyncArg) {
-        let mut current_attempt: u64 = 0;
+    /// Publish a signed message on the content topic of its identifier
+    pub fn publish<T>(
+        &self,
+        graphcast_message: &GraphcastMessage<T>,
+    ) -> Result<String, OperationError>
+    where
+        T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+    {
+        let content_topic = build_content_topics(
+            &self.config.radio_infrastructure().radio_name,
+            0,
+            &[graphcast_message.identifier.clone()],
+        )
+        .first()
+        .cloned()
+        .ok_or_else(|| OperationError::Message(String::from("No content topic for identifier")))?;
+        graphcast_message
+            .send_to_waku(
+                &self.graphcast_agent.node_handle,
+                self.graphcast_agent.pubsub_topic.clone(),
+                content_topic,
+            )
+            .map_err(|e| OperationError::Agent(e.into()))
+    }
 
+    /// Subscribe to the topic of the subgraph and send the upgrade intent
+    pub async fn upgrade_presync(
+        &self,
+        args: &UpgradePresyncArg,
+    ) -> Result<String, OperationError> {
         // Set subscription topic
         let identifier = subgraph_hash_by_id(
             self.config.graph_stack().network_subgraph(),
@@ -55,84 +169,624 @@ impl RadioOperator {
             &args.subgraph_id,
         )
         .await
-        .expect("Failed to match the upgrade intent with an existing subgraph deployment");
+        .map_err(GraphcastAgentError::from)?;
         self.graphcast_agent
             .update_content_topics(vec![identifier])
             .await;
 
-        let mut res = self.gossip_one_shot(args).await;
+        self.gossip_with_retry(args, true).await
+    }
+
+    /// Send the upgrade intent with retries
+    pub async fn gossip_with_retry(
+        &self,
+        args: &UpgradePresyncArg,
+        notify: bool,
+    ) -> Result<String, OperationError> {
+        self.retry_gossip(|| self.gossip_one_shot(args, notify))
+            .await
+    }
+
+    /// radio attempt to send message until success or max at configured retry,
+    /// backing off exponentially with jitter between retryable failures
+    pub async fn retry_gossip<F, Fut>(&self, send: F) -> Result<String, OperationError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<String, OperationError>>,
+    {
+        let mut current_attempt: u64 = 0;
+        let mut res = send().await;
         // Try again if the gossip failed to send while the attempt number is within max_retry
-        while res.is_err() && current_attempt < self.config.max_retry {
+        while let Err(e) = &res {
+            if !is_retryable(e) {
+                error!(
+                    err = tracing::field::debug(e),
+                    "Failed to gossip, not retrying"
+                );
+                break;
+            }
+            if current_attempt >= self.config.max_retry {
+                error!(
+                    err = tracing::field::debug(e),
+                    attempts = current_attempt + 1,
+                    "Failed to gossip, max retry reached"
+                );
+                break;
+            }
+            let delay = retry_delay(
+                self.config.retry_delay,
+                self.config.max_retry_delay,
+                current_attempt,
+            );
             warn!(
-                err = tracing::field::debug(&res),
-                current_attempt, "Failed to gossip, retry"
+                err = tracing::field::debug(e),
+                current_attempt,
+                delay = format!("{}ms", delay.as_millis()),
+                "Failed to gossip, retry"
             );
+            sleep(delay).await;
             current_attempt += 1;
-            res = self.gossip_one_shot(args).await;
+            res = send().await;
         }
+        res
     }
 }
 
+/// Failures to resolve the subgraph or to build and sign the message are
+/// deterministic, while transport and Waku errors may resolve on retry
+pub fn is_retryable(err: &OperationError) -> bool {
+    match err {
+        OperationError::Agent(GraphcastAgentError::QueryResponseError(QueryError::Transport(
+            _,
+        ))) => true,
+        OperationError::Agent(GraphcastAgentError::QueryResponseError(_)) => false,
+        OperationError::Agent(GraphcastAgentError::MessageError(_)) => false,
+        OperationError::Agent(_) => true,
+        _ => false,
+    }
+}
+
+/// Exponential backoff capped at max_delay, randomized between half and the
+/// full delay so that concurrent clients don't retry in lockstep
+pub fn retry_delay(base: Duration, max_delay: Duration, attempt: u64) -> Duration {
+    let exponential = base.saturating_mul(2u32.saturating_pow(attempt.min(31) as u32));
+    let delay = exponential.min(max_delay);
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+/// Resolve the identifier, build and sign the upgrade intent message as
+/// gossip_one_shot would, and print it without starting Waku or sending it
+pub async fn upgrade_presync_dry_run(
+    config: &Config,
+    args: &UpgradePresyncArg,
+) -> Result<(), OperationError> {
+    let wallet = build_wallet(config.wallet_input()?)
+        .map_err(|e| ConfigError::ValidateInput(e.to_string()))?;
+    let time = Utc::now().timestamp();
+    let graph_account = config.graph_stack().graph_account.clone();
+    let identifier = subgraph_hash_by_id(
+        config.graph_stack().network_subgraph(),
+        &graph_account,
+        &args.subgraph_id,
+    )
+    .await
+    .map_err(GraphcastAgentError::from)?;
+
+    let radio_message = UpgradeIntentMessage::build(
+        args.subgraph_id.clone(),
+        args.new_hash.clone(),
+        time,
+        graph_account.clone(),
+    );
+    let domain = config.message_domain();
+    let eip712_hash = domain
+        .eip712_hash(&radio_message)
+        .map_err(|e| OperationError::Message(e.to_string()))?;
+    let graphcast_message = sign_message(
+        &wallet,
+        identifier.clone(),
+        graph_account.clone(),
+        time,
+        radio_message.clone(),
+        &domain,
+    )
+    .map_err(|e| OperationError::Message(e.to_string()))?;
+    let content_topic = build_content_topics(
+        &config.radio_infrastructure().radio_name,
+        0,
+        &[identifier.clone()],
+    )
+    .first()
+    .map(|topic| topic.to_string())
+    .unwrap_or_default();
+
+    let dry_run = json!({
+        "identifier": identifier,
+        "content_topic": content_topic,
+        "sender": wallet_address(&wallet),
+        "graph_account": graph_account,
+        "nonce": time,
+        "payload": radio_message,
+        "eip712_hash": format!("0x{}", hex::encode(eip712_hash)),
+        "signature": graphcast_message.signature,
+        "encoded_message": format!("0x{}", hex::encode(graphcast_message.encode_to_vec())),
+    });
+    info!(
+        identifier,
+        content_topic, "Dry run, the message is not sent"
+    );
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&dry_run).unwrap_or_default()
+    );
+    Ok(())
+}
+
 /// Query the new deployment indexing status at public status endpoints registered
 /// by the indexers who are actively allocating the current deployment
-pub async fn indexing_status(config: &Config, args: &IndexingStatusArg) {
+pub async fn indexing_status(
+    config: &Config,
+    args: &IndexingStatusArg,
+) -> Result<(), OperationError> {
+    // The current deployment is queried alongside to compare the progress of the new deployment
+    let current_deployment =
+        query_subgraph_info(&config.graph_stack().network_subgraph, &args.subgraph_id)
+            .await?
+            .current_deployment;
+
     // Get list of public status APIs
-    let public_status_apis = query_indexer_public_api(&config.graph_stack().network_subgraph, &args.subgraph_id).await
-        .expect("Could not query public status APIs from indexers actively allocated on the network subgraph");
+    let mut deployments: Vec<String> = current_deployment.iter().cloned().collect();
+    if args.include_new_hash_allocations {
+        deployments.push(args.new_hash.clone());
+    }
+    let public_status_apis = query_indexer_public_api(
+        &config.graph_stack().network_subgraph,
+        &deployments,
+        &args.allocation_status,
+    )
+    .await?;
     info!(
         num_apis = public_status_apis.len(),
         "Number of APIs to query indexing status"
     );
 
-    // Query all the public status APIs for new_hash indexing_status
-    let new_hash_statuses = query_indexing_statuses(public_status_apis, &args.new_hash).await;
+    let client = StatusClient::new(
+        args.max_concurrent_queries,
+        args.connect_timeout,
+        args.query_timeout,
+        args.query_retries,
+    )?;
+    let notifier = Notifier::from_config(config.radio_infrastructure());
+    let store = Store::from_config(config);
+    if args.watch {
+        return watch_indexing_status(
+            args,
+            &client,
+            public_status_apis,
+            current_deployment.as_deref(),
+            &notifier,
+            &store,
+        )
+        .await;
+    }
+    let report = status_report(
+        args,
+        &client,
+        public_status_apis,
+        current_deployment.as_deref(),
+    )
+    .await;
+    store.record_snapshot(StatusSnapshot::new(&args.subgraph_id, &report));
+    notifier.notify(report.summary_message()).await;
+    if let Some(format) = &args.output {
+        println!("{}", report.render(format));
+    }
+
+    if args.poi_check {
+        match poi_check(args, &client, &report.indexers).await {
+            Some(poi_report) => {
+                if let Some(format) = &args.output {
+                    println!("{}", poi_report.render(format));
+                }
+            }
+            None => warn!("No indexer reported a status to cross-check proofs of indexing"),
+        }
+    }
+    Ok(())
+}
+
+/// Print the sent messages and status snapshots recorded in the state file
+pub fn history(config: &Config, args: &HistoryArg) -> Result<(), OperationError> {
+    let store = Store::from_config(config);
+    let mut state = store.load()?;
+    if let Some(subgraph_id) = &args.subgraph_id {
+        state
+            .sent_messages
+            .retain(|m| m.subgraph_id == *subgraph_id);
+        state
+            .status_snapshots
+            .retain(|s| s.subgraph_id == *subgraph_id);
+    }
+    debug!(
+        path = store.path().display().to_string(),
+        num_sent_messages = state.sent_messages.len(),
+        num_status_snapshots = state.status_snapshots.len(),
+        "Loaded state file"
+    );
+    println!("{}", render_history(&state, &args.output));
+    Ok(())
+}
+
+/// Print a sent message recorded in the state file along with the status
+/// snapshots of its new hash
+pub fn show(config: &Config, args: &ShowArg) -> Result<(), OperationError> {
+    let state = Store::from_config(config).load()?;
+    let message = show_message(&state, &args.msg_id)?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&message).unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Query all the public status APIs for new_hash and current deployment indexing
+/// statuses and summarize the results
+pub async fn status_report(
+    args: &IndexingStatusArg,
+    client: &StatusClient,
+    public_status_apis: Vec<IndexerUrl>,
+    current_deployment: Option<&str>,
+) -> StatusReport {
+    let (new_hash_statuses, current_statuses) = tokio::join!(
+        query_indexing_statuses(client, public_status_apis.clone(), &args.new_hash),
+        async {
+            match current_deployment {
+                Some(deployment) => {
+                    query_indexing_statuses(client, public_status_apis.clone(), deployment).await
+                }
+                None => vec![],
+            }
+        }
+    );
     debug!("new_hash_statuses {:#?}", new_hash_statuses);
-    // Summarize the results
-    summarize_indexing_statuses(new_hash_statuses);
+    let current_blocks = current_blocks(&current_statuses, args.network.as_deref());
+    let summary = summarize_indexing_statuses(
+        &public_status_apis,
+        &new_hash_statuses,
+        &current_blocks,
+        args.network.as_deref(),
+    );
+
+    StatusReport {
+        deployment: args.new_hash.clone(),
+        current_deployment: current_deployment.map(String::from),
+        indexers: new_hash_statuses
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .flat_map(|indexer| {
+                IndexerStatusRow::rows(indexer, args.network.as_deref(), &current_blocks)
+            })
+            .collect(),
+        summary,
+    }
 }
 
-/// Summarize indexing statuses: Number of indexers,
-pub fn summarize_indexing_statuses(statuses: Vec<Result<IndexerInfo, QueryError>>) {
+/// Number of unsynced indexers with the largest allocations to report
+const NUM_LARGEST_ALLOCATORS_BEHIND: usize = 5;
+
+/// Summarize indexing statuses: Number of indexers, synced indexers, and the
+/// progress overall and of each chain. Statuses are in the order of indexer_urls
+pub fn summarize_indexing_statuses(
+    indexer_urls: &[IndexerUrl],
+    statuses: &[Result<IndexerInfo, StatusQueryError>],
+    current_blocks: &CurrentBlocks,
+    network: Option<&str>,
+) -> StatusSummary {
+    // Indexers with no chain matching the network are not indexing the deployment
     let okay_results: Vec<IndexerInfo> = statuses
         .iter()
         .filter_map(|r| r.as_ref().ok())
+        .filter(|indexer| !indexer.chains(network).is_empty())
         .cloned()
         .collect();
     let synced_indexers = okay_results
         .iter()
         .filter(|&indexer| indexer.status.synced)
         .count();
-    //TODO: to support multiple chains, check chains id specifications, right now brutally taking the first chain
-    let block_progress: String = okay_results
-        .iter()
-        .max_by_key(|indexer| indexer.latest_block_number())
-        .map(|indexer| {
-            let chain_head = indexer.chain_head_block_number();
-            let latest = indexer.latest_block_number();
-            format!("{} / {}", chain_head, latest)
-        })
-        .unwrap_or(String::from("N/A"));
     let num_indexing_indexers = okay_results.len();
-    let avg_progress: f32 = okay_results
+    let progresses: Vec<f32> = okay_results
         .iter()
-        .map(|indexer| {
-            let progress = indexer.latest_block_number() as f32
-                / indexer.chain_head_block_number() as f32
-                * 100.0;
+        .filter_map(|indexer| {
+            let progress = indexer.progress(network)?;
             debug!(
                 indexer = tracing::field::debug(&indexer.info),
                 progress, "Indexer statuses"
             );
-            progress
+            Some(progress)
+        })
+        .collect();
+    let avg_progress = average(&progresses);
+    let total_stake: f64 = okay_results
+        .iter()
+        .map(|indexer| indexer.info.staked_tokens)
+        .sum();
+    let stake_weighted_progress = if total_stake > 0.0 {
+        (okay_results
+            .iter()
+            .filter_map(|indexer| {
+                Some(indexer.info.staked_tokens * indexer.progress(network)? as f64)
+            })
+            .sum::<f64>()
+            / total_stake) as f32
+    } else {
+        0.0
+    };
+    let total_allocated_tokens: f64 = indexer_urls
+        .iter()
+        .map(|indexer_url| indexer_url.allocated_tokens)
+        .sum();
+    let synced_allocated_tokens: f64 = okay_results
+        .iter()
+        .filter(|indexer| indexer.status.synced)
+        .map(|indexer| indexer.info.allocated_tokens)
+        .sum();
+    let synced_allocation_share = if total_allocated_tokens > 0.0 {
+        (synced_allocated_tokens / total_allocated_tokens * 100.0) as f32
+    } else {
+        0.0
+    };
+    let mut largest_allocators_behind: Vec<AllocatorBehind> = okay_results
+        .iter()
+        .filter(|indexer| !indexer.status.synced)
+        .map(|indexer| AllocatorBehind {
+            indexer_id: indexer.info.id.clone(),
+            allocated_tokens: indexer.info.allocated_tokens,
+            progress: indexer.progress(network).unwrap_or_default(),
+        })
+        .collect();
+    largest_allocators_behind.sort_by(|a, b| b.allocated_tokens.total_cmp(&a.allocated_tokens));
+    largest_allocators_behind.truncate(NUM_LARGEST_ALLOCATORS_BEHIND);
+    for allocator in &largest_allocators_behind {
+        debug!(
+            indexer = allocator.indexer_id,
+            allocated_tokens = allocator.allocated_tokens,
+            progress = allocator.progress,
+            "Large allocator behind"
+        );
+    }
+
+    let caught_up_indexers = okay_results
+        .iter()
+        .filter(|indexer| {
+            indexer.chains(network).iter().all(|chain| {
+                current_blocks
+                    .get(&(indexer.info.id.clone(), chain.network.clone()))
+                    .is_some_and(|current| chain.latest_block_number() >= *current)
+            })
+        })
+        .count();
+
+    let mut networks: Vec<&str> = okay_results
+        .iter()
+        .flat_map(|indexer| indexer.chains(network))
+        .map(|chain| chain.network.as_str())
+        .collect();
+    networks.sort();
+    networks.dedup();
+    let chains: Vec<ChainSummary> = networks
+        .into_iter()
+        .map(|chain_network| {
+            let chain_statuses: Vec<&ChainStatus> = okay_results
+                .iter()
+                .flat_map(|indexer| indexer.chains(Some(chain_network)))
+                .collect();
+            let block_progress: String = chain_statuses
+                .iter()
+                .max_by_key(|chain| chain.latest_block_number())
+                .map(|chain| {
+                    let chain_head = chain.chain_head_block_number();
+                    let latest = chain.latest_block_number();
+                    format!("{} / {}", chain_head, latest)
+                })
+                .unwrap_or(String::from("N/A"));
+            let chain_progresses: Vec<f32> = chain_statuses
+                .iter()
+                .map(|chain| chain.progress())
+                .collect();
+            let chain_summary = ChainSummary {
+                network: chain_network.to_string(),
+                num_indexing_indexers: chain_statuses.len(),
+                latest_synced_block: block_progress,
+                average_progress: average(&chain_progresses),
+            };
+            info!(
+                network = chain_summary.network,
+                num_indexing_indexers = chain_summary.num_indexing_indexers,
+                latest_synced_block = chain_summary.latest_synced_block,
+                average_progress = format!("{}%", chain_summary.average_progress),
+                "Chain indexing statuses summary"
+            );
+            chain_summary
         })
-        .sum::<f32>()
-        / num_indexing_indexers as f32;
+        .collect();
+
+    // Group fatal errors so that a broken version shows up as a single cause
+    let mut fatal_errors: Vec<FatalErrorSummary> = vec![];
+    for error in okay_results
+        .iter()
+        .filter_map(|indexer| indexer.status.fatal_error.as_ref())
+    {
+        let block_number = error.block_number();
+        match fatal_errors.iter_mut().find(|e| {
+            e.message == error.message
+                && e.block_number == block_number
+                && e.handler == error.handler
+                && e.deterministic == error.deterministic
+        }) {
+            Some(summary) => summary.num_indexers += 1,
+            None => fatal_errors.push(FatalErrorSummary {
+                message: error.message.clone(),
+                block_number,
+                handler: error.handler.clone(),
+                deterministic: error.deterministic,
+                num_indexers: 1,
+            }),
+        }
+    }
+    fatal_errors.sort_by(|a, b| b.num_indexers.cmp(&a.num_indexers));
+    for fatal_error in &fatal_errors {
+        warn!("{}", fatal_error);
+    }
+    let num_indexers_with_non_fatal_errors = okay_results
+        .iter()
+        .filter(|indexer| !indexer.status.non_fatal_errors.is_empty())
+        .count();
+
+    let mut unreachable_indexers: Vec<UnreachableSummary> = vec![];
+    for (indexer_url, e) in indexer_urls
+        .iter()
+        .zip(statuses)
+        .filter_map(|(indexer_url, r)| Some((indexer_url, r.as_ref().err()?)))
+    {
+        let Some(failure) = e.endpoint_failure() else {
+            continue;
+        };
+        debug!(
+            indexer = indexer_url.id,
+            url = indexer_url.url,
+            failure = failure.to_string(),
+            err = e.to_string(),
+            "Unreachable status endpoint"
+        );
+        match unreachable_indexers
+            .iter_mut()
+            .find(|u| u.failure == failure)
+        {
+            Some(unreachable) => unreachable.indexers.push(indexer_url.id.clone()),
+            None => unreachable_indexers.push(UnreachableSummary {
+                failure,
+                indexers: vec![indexer_url.id.clone()],
+            }),
+        }
+    }
+    unreachable_indexers.sort_by_key(|u| u.failure);
+    for unreachable in &unreachable_indexers {
+        warn!(
+            failure = unreachable.failure.to_string(),
+            num_indexers = unreachable.indexers.len(),
+            indexers = tracing::field::debug(&unreachable.indexers),
+            "Indexers with unreachable status endpoints"
+        );
+    }
+
     info!(
         num_currently_allocated_indexer_apis = statuses.len(),
         num_indexing_indexers = num_indexing_indexers,
         num_synced_indexers = synced_indexers,
-        latest_synced_block = block_progress,
+        num_caught_up_indexers = caught_up_indexers,
+        num_failed_indexers = fatal_errors.iter().map(|e| e.num_indexers).sum::<usize>(),
+        num_indexers_with_non_fatal_errors,
+        num_unreachable_indexers = unreachable_indexers
+            .iter()
+            .map(|u| u.indexers.len())
+            .sum::<usize>(),
+        stake_weighted_progress = format!("{}%", stake_weighted_progress),
+        synced_allocation_share = format!("{}%", synced_allocation_share),
+        num_chains = chains.len(),
         average_progress = format!("{}%", avg_progress),
         "Indexing statuses summary"
     );
+    StatusSummary {
+        num_currently_allocated_indexer_apis: statuses.len(),
+        num_indexing_indexers,
+        num_synced_indexers: synced_indexers,
+        num_caught_up_indexers: caught_up_indexers,
+        average_progress: avg_progress,
+        chains,
+        fatal_errors,
+        num_indexers_with_non_fatal_errors,
+        stake_weighted_progress,
+        synced_allocation_share,
+        largest_allocators_behind,
+        unreachable_indexers,
+    }
+}
+
+fn average(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::validation::ValidationError;
+    use graphcast_sdk::graphcast_agent::message_typing::BuildMessageError;
+
+    #[test]
+    fn retryable_errors() {
+        let transport = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let cases = [
+            (
+                OperationError::Agent(GraphcastAgentError::QueryResponseError(
+                    QueryError::Transport(transport),
+                )),
+                true,
+            ),
+            (
+                OperationError::Agent(GraphcastAgentError::QueryResponseError(
+                    QueryError::ParseResponseError(String::from("No subgraph")),
+                )),
+                false,
+            ),
+            (
+                OperationError::Agent(GraphcastAgentError::MessageError(
+                    BuildMessageError::InvalidFields(anyhow::anyhow!("Invalid nonce")),
+                )),
+                false,
+            ),
+            (
+                OperationError::Config(ConfigError::ValidateInput(String::from(
+                    "Must provide either private key or mnemonic",
+                ))),
+                false,
+            ),
+            (
+                OperationError::Validation(ValidationError::InvalidHash(String::from("Qm"))),
+                false,
+            ),
+            (
+                OperationError::Message(String::from("Invalid signature")),
+                false,
+            ),
+            (OperationError::Timeout(Duration::from_secs(60)), false),
+        ];
+        for (err, retryable) in cases {
+            assert_eq!(is_retryable(&err), retryable, "{err:?}");
+        }
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_max_with_jitter() {
+        let base = Duration::from_millis(100);
+        let max_delay = Duration::from_millis(1000);
+        let capped = [100, 200, 400, 800, 1000, 1000];
+        for (attempt, capped) in capped.into_iter().enumerate() {
+            let capped = Duration::from_millis(capped);
+            for _ in 0..20 {
+                let delay = retry_delay(base, max_delay, attempt as u64);
+                assert!(
+                    delay >= capped / 2 && delay <= capped,
+                    "attempt {attempt}: {delay:?} outside of [{:?}, {capped:?}]",
+                    capped / 2
+                );
+            }
+        }
+        // Attempts past the shift limit stay capped instead of overflowing
+        assert!(retry_delay(base, max_delay, u64::MAX) <= max_delay);
+        assert!(retry_delay(Duration::MAX, max_delay, 3) <= max_delay);
+    }
 }
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+use crate::config::{IndexingStatusArg, OutputFormat};
+use crate::operator::summary::{csv_field, IndexerStatusRow};
+use crate::query::{query_proofs_of_indexing, IndexerPoi, IndexerUrl, StatusClient};
+
+/// Indexers that computed the same proof of indexing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoiGroup {
+    pub proof_of_indexing: String,
+    pub indexers: Vec<String>,
+}
+
+/// Proofs of indexing of a deployment at a common block, grouped by value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoiReport {
+    pub deployment: String,
+    pub network: String,
+    pub block_number: u64,
+    /// Largest group first, the remaining groups diverge from it
+    pub groups: Vec<PoiGroup>,
+    /// Proof of indexing of the largest group, none when several groups tie
+    pub majority: Option<String>,
+    pub divergent_indexers: Vec<String>,
+    /// Indexers without a latest block or with a fatal error, left out of the
+    /// comparison
+    pub skipped_indexers: Vec<String>,
+    pub num_errors: usize,
+}
+
+/// Cross-check the proofs of indexing of the indexers reporting a status for the
+/// deployment, at the latest block every one of them has indexed. Indexers that
+/// have not started or have failed would hold the block back and are skipped
+pub async fn poi_check(
+    args: &IndexingStatusArg,
+    client: &StatusClient,
+    rows: &[IndexerStatusRow],
+) -> Option<PoiReport> {
+    // Proofs of indexing are compared on a single chain
+    let network = args
+        .network
+        .clone()
+        .or_else(|| rows.first().map(|row| row.network.clone()))?;
+    let (rows, skipped): (Vec<&IndexerStatusRow>, Vec<&IndexerStatusRow>) = rows
+        .iter()
+        .filter(|row| row.network == network)
+        .partition(|row| row.latest_block > 0 && row.fatal_error.is_none());
+    let skipped_indexers: Vec<String> = skipped.iter().map(|row| row.indexer_id.clone()).collect();
+    if !skipped_indexers.is_empty() {
+        info!(
+            skipped_indexers = tracing::field::debug(&skipped_indexers),
+            "Leaving indexers without a latest block or with a fatal error out of the proof of indexing check"
+        );
+    }
+    let block_number = rows.iter().map(|row| row.latest_block).min()?;
+    let indexer_urls = rows
+        .iter()
+        .map(|row| IndexerUrl {
+            id: row.indexer_id.clone(),
+            url: row.url.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    let results =
+        query_proofs_of_indexing(client, indexer_urls, &args.new_hash, block_number).await;
+    let groups = group_proofs(results.iter().filter_map(|r| r.as_ref().ok()));
+    let majority = majority(&groups);
+    let divergent_indexers: Vec<String> = match &majority {
+        Some(_) => groups
+            .iter()
+            .skip(1)
+            .flat_map(|group| group.indexers.clone())
+            .collect(),
+        None => vec![],
+    };
+
+    let report = PoiReport {
+        deployment: args.new_hash.clone(),
+        network,
+        block_number,
+        groups,
+        majority,
+        divergent_indexers,
+        skipped_indexers,
+        num_errors: results.iter().filter(|r| r.is_err()).count(),
+    };
+    for group in &report.groups {
+        info!(
+            block_number = report.block_number,
+            proof_of_indexing = group.proof_of_indexing,
+            indexers = tracing::field::debug(&group.indexers),
+            "Proof of indexing group"
+        );
+    }
+    if report.majority.is_none() && report.groups.len() > 1 {
+        warn!(
+            block_number = report.block_number,
+            "No proof of indexing is computed by more indexers than the others"
+        );
+    }
+    if !report.divergent_indexers.is_empty() {
+        warn!(
+            block_number = report.block_number,
+            divergent_indexers = tracing::field::debug(&report.divergent_indexers),
+            "Indexers diverge from the most common proof of indexing"
+        );
+    }
+    info!(
+        network = report.network,
+        block_number = report.block_number,
+        num_groups = report.groups.len(),
+        num_divergent_indexers = report.divergent_indexers.len(),
+        num_errors = report.num_errors,
+        "Proof of indexing cross-check summary"
+    );
+    Some(report)
+}
+
+/// Group indexers by proof of indexing, largest group first. Groups of the same
+/// size are ordered by proof of indexing so that reports are reproducible
+fn group_proofs<'a>(pois: impl Iterator<Item = &'a IndexerPoi>) -> Vec<PoiGroup> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for poi in pois {
+        groups
+            .entry(poi.proof_of_indexing.clone())
+            .or_default()
+            .push(poi.info.id.clone());
+    }
+    let mut groups: Vec<PoiGroup> = groups
+        .into_iter()
+        .map(|(proof_of_indexing, mut indexers)| {
+            indexers.sort();
+            PoiGroup {
+                proof_of_indexing,
+                indexers,
+            }
+        })
+        .collect();
+    // Stable sort keeps the proof of indexing order within a size
+    groups.sort_by(|a, b| b.indexers.len().cmp(&a.indexers.len()));
+    groups
+}
+
+/// Proof of indexing of the largest group, if no other group has as many indexers
+fn majority(groups: &[PoiGroup]) -> Option<String> {
+    match groups {
+        [first, second, ..] if first.indexers.len() == second.indexers.len() => None,
+        [first, ..] => Some(first.proof_of_indexing.clone()),
+        [] => None,
+    }
+}
+
+impl PoiReport {
+    pub fn render(&self, format: &OutputFormat) -> String {
+        let rows = self.groups.iter().flat_map(|group| {
+            group.indexers.iter().map(move |indexer| {
+                (
+                    indexer,
+                    &group.proof_of_indexing,
+                    self.divergent_indexers.contains(indexer),
+                )
+            })
+        });
+        match format {
+            OutputFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            OutputFormat::Csv => std::iter::once(String::from(
+                "indexer_id,block_number,proof_of_indexing,divergent",
+            ))
+            .chain(rows.map(|(indexer, poi, divergent)| {
+                format!(
+                    "{},{},{},{}",
+                    csv_field(indexer),
+                    self.block_number,
+                    csv_field(poi),
+                    divergent
+                )
+            }))
+            .collect::<Vec<String>>()
+            .join("\n"),
+            OutputFormat::Table => std::iter::once(format!(
+                "Proofs of indexing of {} on {} at block {}",
+                self.deployment, self.network, self.block_number
+            ))
+            .chain(rows.map(|(indexer, poi, divergent)| {
+                format!(
+                    "{}  {}{}",
+                    indexer,
+                    poi,
+                    if divergent { "  (divergent)" } else { "" }
+                )
+            }))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(indexer: &str, proof_of_indexing: &str) -> IndexerPoi {
+        IndexerPoi {
+            info: IndexerUrl {
+                id: indexer.to_string(),
+                ..Default::default()
+            },
+            block_hash: String::from("0x01"),
+            proof_of_indexing: proof_of_indexing.to_string(),
+        }
+    }
+
+    #[test]
+    fn largest_group_is_the_majority() {
+        let pois = [poi("c", "0xaa"), poi("a", "0xbb"), poi("b", "0xaa")];
+        let groups = group_proofs(pois.iter());
+
+        assert_eq!(groups[0].proof_of_indexing, "0xaa");
+        assert_eq!(groups[0].indexers, vec!["b", "c"]);
+        assert_eq!(groups[1].indexers, vec!["a"]);
+        assert_eq!(majority(&groups).as_deref(), Some("0xaa"));
+    }
+
+    #[test]
+    fn tied_groups_have_no_majority() {
+        let pois = [poi("b", "0xbb"), poi("a", "0xaa")];
+        for pois in [pois.clone(), [pois[1].clone(), pois[0].clone()]] {
+            let groups = group_proofs(pois.iter());
+            assert_eq!(groups[0].proof_of_indexing, "0xaa");
+            assert_eq!(groups[1].proof_of_indexing, "0xbb");
+            assert_eq!(majority(&groups), None);
+        }
+        assert_eq!(majority(&[]), None);
+    }
+}
@@ -0,0 +1,86 @@
+use graphcast_sdk::graphcast_agent::GraphcastAgentError;
+use graphcast_sdk::graphql::client_graph_account::subgraph_hash_by_id;
+
+use crate::config::{Config, UpgradeCancelArg};
+use crate::messages::rollback::RollbackIntentMessage;
+use crate::operator::validation::ValidationError;
+use crate::operator::{OperationError, RadioOperator};
+use crate::store::Store;
+
+impl RadioOperator {
+    pub async fn send_rollback_intent(
+        &self,
+        args: &UpgradeCancelArg,
+        intent_nonce: i64,
+    ) -> Result<String, OperationError> {
+        let graph_account = self.config.graph_stack().graph_account.clone();
+        self.send_and_record(
+            "RollbackIntentMessage",
+            &args.subgraph_id,
+            &args.new_hash,
+            |time| {
+                RollbackIntentMessage::build(
+                    args.subgraph_id.clone(),
+                    args.new_hash.clone(),
+                    intent_nonce,
+                    time,
+                    graph_account,
+                    args.reason.clone(),
+                )
+            },
+            Some(&format!(
+                "for subgraph {} retracting new deployment {} (nonce {})",
+                args.subgraph_id, args.new_hash, intent_nonce
+            )),
+        )
+        .await
+    }
+
+    /// Subscribe to the topic of the subgraph and retract the upgrade intent with
+    /// the given nonce
+    pub async fn upgrade_cancel(
+        &self,
+        args: &UpgradeCancelArg,
+        intent_nonce: i64,
+    ) -> Result<String, OperationError> {
+        let identifier = subgraph_hash_by_id(
+            self.config.graph_stack().network_subgraph(),
+            &self.config.graph_stack().graph_account,
+            &args.subgraph_id,
+        )
+        .await
+        .map_err(GraphcastAgentError::from)?;
+        self.graphcast_agent
+            .update_content_topics(vec![identifier])
+            .await;
+
+        self.retry_gossip(|| self.send_rollback_intent(args, intent_nonce))
+            .await
+    }
+}
+
+/// Nonce of the retracted upgrade intent, defaulting to the latest intent for the
+/// new hash recorded in the state file. Resolved before starting the Waku node
+pub fn intent_nonce(config: &Config, args: &UpgradeCancelArg) -> Result<i64, OperationError> {
+    if let Some(nonce) = args.nonce {
+        return Ok(nonce);
+    }
+    Store::from_config(config)
+        .load()?
+        .sent_messages
+        .iter()
+        .filter(|m| {
+            m.message_type == "UpgradeIntentMessage"
+                && m.subgraph_id == args.subgraph_id
+                && m.new_hash == args.new_hash
+        })
+        .map(|m| m.nonce)
+        .max()
+        .ok_or_else(|| {
+            ValidationError::UnknownIntent {
+                subgraph_id: args.subgraph_id.clone(),
+                new_hash: args.new_hash.clone(),
+            }
+            .into()
+        })
+}
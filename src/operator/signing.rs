@@ -0,0 +1,217 @@
+use async_graphql::OutputType;
+use chrono::Utc;
+use ethers::utils::hex;
+use ethers_core::types::transaction::eip712::Eip712;
+use graphcast_sdk::graphcast_agent::{message_typing::GraphcastMessage, GraphcastAgentError};
+use graphcast_sdk::graphql::client_graph_account::subgraph_hash_by_id;
+use graphcast_sdk::{build_wallet, wallet_address};
+use prost::Message;
+use serde::Serialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::{Config, ConfigError, MessageSignArg, MessageVerifyArg};
+use crate::messages::domain::{recover_signer, sign_message};
+use crate::messages::upgrade::UpgradeIntentMessage;
+use crate::messages::DecodedMessage;
+use crate::operator::OperationError;
+
+/// Message envelope with the signer recovered from its EIP-712 signature
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifiedMessage {
+    pub message_type: String,
+    pub identifier: String,
+    pub nonce: i64,
+    pub graph_account: String,
+    pub signer: Option<String>,
+    /// Graph account configured for the CLI
+    pub expected_graph_account: String,
+    /// Whether the message is sent on behalf of the configured graph account
+    pub graph_account_matches: bool,
+    /// Whether the sender passes the configured identity validation, as checked
+    /// by receiving radios
+    pub valid_sender: bool,
+    pub sender_error: Option<String>,
+    pub eip712_hash: Option<String>,
+    pub signature: String,
+    pub payload: serde_json::Value,
+}
+
+/// Build and sign an upgrade intent with the configured wallet and write the
+/// protobuf encoded envelope to a file, without starting Waku
+pub async fn message_sign(config: &Config, args: &MessageSignArg) -> Result<(), OperationError> {
+    let wallet = build_wallet(config.wallet_input()?)
+        .map_err(|e| ConfigError::ValidateInput(e.to_string()))?;
+    let time = Utc::now().timestamp();
+    let graph_account = config.graph_stack().graph_account.clone();
+    let identifier = match &args.identifier {
+        Some(identifier) => identifier.clone(),
+        None => subgraph_hash_by_id(
+            config.graph_stack().network_subgraph(),
+            &graph_account,
+            &args.subgraph_id,
+        )
+        .await
+        .map_err(GraphcastAgentError::from)?,
+    };
+
+    let radio_message = UpgradeIntentMessage::build(
+        args.subgraph_id.clone(),
+        args.new_hash.clone(),
+        time,
+        graph_account.clone(),
+    );
+    let graphcast_message = sign_message(
+        &wallet,
+        identifier.clone(),
+        graph_account.clone(),
+        time,
+        radio_message.clone(),
+        &config.message_domain(),
+    )
+    .map_err(|e| OperationError::Message(e.to_string()))?;
+    std::fs::write(&args.file, graphcast_message.encode_to_vec())
+        .map_err(|e| OperationError::Message(format!("Could not write {}: {}", args.file, e)))?;
+
+    info!(identifier, file = args.file, "Signed message");
+    let signed = json!({
+        "file": args.file,
+        "identifier": identifier,
+        "sender": wallet_address(&wallet),
+        "graph_account": graph_account,
+        "nonce": time,
+        "payload": radio_message,
+        "signature": graphcast_message.signature,
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&signed).unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Decode a message envelope, recover its signer and validate it the way
+/// receiving radios do, for the configured graph account
+pub async fn message_verify(
+    config: &Config,
+    args: &MessageVerifyArg,
+) -> Result<(), OperationError> {
+    let envelope = read_envelope(args.file.as_deref(), args.payload.as_deref())?;
+    let verified = match DecodedMessage::decode(&envelope) {
+        DecodedMessage::UpgradeIntent(m) => verify_message(config, m, "UpgradeIntentMessage").await,
+        DecodedMessage::DeprecationNotice(m) => {
+            verify_message(config, m, "DeprecationNoticeMessage").await
+        }
+        DecodedMessage::RollbackIntent(m) => {
+            verify_message(config, m, "RollbackIntentMessage").await
+        }
+        DecodedMessage::Unknown(_) => {
+            return Err(OperationError::Message(String::from(
+                "Envelope does not contain a known Graphcast message",
+            )))
+        }
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&verified).unwrap_or_default()
+    );
+
+    if let Some(e) = &verified.sender_error {
+        warn!(
+            signer = verified.signer,
+            err = e,
+            "Message sender does not pass identity validation"
+        );
+        return Err(OperationError::Message(format!(
+            "Sender {} is not valid: {}",
+            verified.signer.as_deref().unwrap_or("unknown"),
+            e
+        )));
+    }
+    if !verified.graph_account_matches {
+        warn!(
+            graph_account = verified.graph_account,
+            expected_graph_account = verified.expected_graph_account,
+            "Message is not sent on behalf of the configured graph account"
+        );
+        return Err(OperationError::Message(format!(
+            "Graph account {} does not match the configured graph account {}",
+            verified.graph_account, verified.expected_graph_account
+        )));
+    }
+    info!(
+        signer = verified.signer,
+        message_type = verified.message_type,
+        "Verified message sender"
+    );
+    Ok(())
+}
+
+/// Read a message envelope from a file, protobuf encoded or as hex, or from a
+/// hex payload
+pub fn read_envelope(file: Option<&str>, payload: Option<&str>) -> Result<Vec<u8>, OperationError> {
+    let bytes = match (file, payload) {
+        (Some(file), _) => std::fs::read(file)
+            .map_err(|e| OperationError::Message(format!("Could not read {}: {}", file, e)))?,
+        (None, Some(payload)) => payload.as_bytes().to_vec(),
+        (None, None) => {
+            return Err(OperationError::Message(String::from(
+                "Provide a message file or payload",
+            )))
+        }
+    };
+    // Encoded envelopes start with a field tag, which is never a hex digit
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.trim();
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return hex::decode(text)
+            .map_err(|e| OperationError::Message(format!("Invalid hex payload: {}", e)));
+    }
+    Ok(bytes)
+}
+
+/// Recover the signer under the configured domain and run the identity
+/// validation of receiving radios. The envelope may have been signed with the
+/// Graphcast ID of this host, so it is not rejected as sent by itself
+pub(crate) async fn verify_message<T>(
+    config: &Config,
+    graphcast_message: GraphcastMessage<T>,
+    message_type: &str,
+) -> VerifiedMessage
+where
+    T: Message + Eip712 + Default + Clone + Serialize + OutputType + 'static,
+{
+    let domain = config.message_domain();
+    let signer = recover_signer(&graphcast_message, &domain).ok();
+    let sender_error = graphcast_message
+        .valid_sender(
+            config.graph_stack().registry_subgraph(),
+            config.graph_stack().network_subgraph(),
+            String::new(),
+            &config.radio_infrastructure().id_validation,
+        )
+        .await
+        .err()
+        .map(|e| e.to_string());
+    let expected_graph_account = config.graph_stack().graph_account.clone();
+    VerifiedMessage {
+        message_type: String::from(message_type),
+        identifier: graphcast_message.identifier.clone(),
+        nonce: graphcast_message.nonce,
+        graph_account: graphcast_message.graph_account.clone(),
+        signer,
+        graph_account_matches: graphcast_message
+            .graph_account
+            .eq_ignore_ascii_case(&expected_graph_account),
+        expected_graph_account,
+        valid_sender: sender_error.is_none(),
+        sender_error,
+        eip712_hash: domain
+            .eip712_hash(&graphcast_message.payload)
+            .ok()
+            .map(|hash| format!("0x{}", hex::encode(hash))),
+        signature: graphcast_message.signature.clone(),
+        payload: serde_json::to_value(&graphcast_message.payload).unwrap_or_default(),
+    }
+}
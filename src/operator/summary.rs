@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::OutputFormat;
+use crate::query::{ChainStatus, EndpointFailure, IndexerInfo, StatusQueryError, SubgraphHealth};
+
+/// Latest blocks of the current deployment keyed by indexer id and network
+pub type CurrentBlocks = HashMap<(String, String), u64>;
+
+/// Collect the latest blocks of the current deployment to compare the new deployment against
+pub fn current_blocks(
+    current_statuses: &[Result<IndexerInfo, StatusQueryError>],
+    network: Option<&str>,
+) -> CurrentBlocks {
+    current_statuses
+        .iter()
+        .filter_map(|r| r.as_ref().ok())
+        .flat_map(|indexer| {
+            indexer.chains(network).into_iter().map(|chain| {
+                (
+                    (indexer.info.id.clone(), chain.network.clone()),
+                    chain.latest_block_number(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Indexing status of a deployment at a single indexer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerStatusRow {
+    pub indexer_id: String,
+    pub url: String,
+    pub health: SubgraphHealth,
+    pub synced: bool,
+    pub network: String,
+    pub latest_block: u64,
+    pub chain_head_block: u64,
+    pub progress: f32,
+    /// Latest block of the current deployment at the indexer
+    pub current_block: Option<u64>,
+    /// Blocks the new deployment is behind the current deployment
+    pub blocks_behind_current: Option<i64>,
+    pub fatal_error: Option<String>,
+    pub num_non_fatal_errors: usize,
+    pub staked_tokens: f64,
+    pub allocated_tokens: f64,
+}
+
+impl IndexerStatusRow {
+    /// Rows of an indexer, one per chain indexed by the deployment
+    pub fn rows(
+        indexer: &IndexerInfo,
+        network: Option<&str>,
+        current_blocks: &CurrentBlocks,
+    ) -> Vec<IndexerStatusRow> {
+        indexer
+            .chains(network)
+            .into_iter()
+            .map(|chain| IndexerStatusRow::new(indexer, chain, current_blocks))
+            .collect()
+    }
+
+    fn new(indexer: &IndexerInfo, chain: &ChainStatus, current_blocks: &CurrentBlocks) -> Self {
+        let latest_block = chain.latest_block_number();
+        let current_block = current_blocks
+            .get(&(indexer.info.id.clone(), chain.network.clone()))
+            .copied();
+        IndexerStatusRow {
+            indexer_id: indexer.info.id.clone(),
+            url: indexer.info.url.clone(),
+            health: indexer.status.health.clone(),
+            synced: indexer.status.synced,
+            network: chain.network.clone(),
+            latest_block,
+            chain_head_block: chain.chain_head_block_number(),
+            progress: chain.progress(),
+            current_block,
+            blocks_behind_current: current_block
+                .map(|current| current as i64 - latest_block as i64),
+            fatal_error: indexer.status.fatal_error.as_ref().map(|e| e.to_string()),
+            num_non_fatal_errors: indexer.status.non_fatal_errors.len(),
+            staked_tokens: indexer.info.staked_tokens,
+            allocated_tokens: indexer.info.allocated_tokens,
+        }
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.indexer_id.clone(),
+            self.url.clone(),
+            format!("{:?}", self.health),
+            self.synced.to_string(),
+            self.network.clone(),
+            self.latest_block.to_string(),
+            self.chain_head_block.to_string(),
+            format!("{:.2}%", self.progress),
+            self.current_block
+                .map_or(String::from("N/A"), |block| block.to_string()),
+            self.blocks_behind_current
+                .map_or(String::from("N/A"), |blocks| blocks.to_string()),
+            self.fatal_error.clone().unwrap_or_default(),
+            self.num_non_fatal_errors.to_string(),
+            format!("{:.0}", self.staked_tokens),
+            format!("{:.0}", self.allocated_tokens),
+        ]
+    }
+}
+
+/// Aggregated progress of a single chain across the queried indexers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSummary {
+    pub network: String,
+    pub num_indexing_indexers: usize,
+    pub latest_synced_block: String,
+    pub average_progress: f32,
+}
+
+/// Indexers that failed with the same fatal error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FatalErrorSummary {
+    pub message: String,
+    pub block_number: Option<u64>,
+    pub handler: Option<String>,
+    pub deterministic: bool,
+    pub num_indexers: usize,
+}
+
+impl std::fmt::Display for FatalErrorSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} indexers failed {}",
+            self.num_indexers,
+            if self.deterministic {
+                "deterministically"
+            } else {
+                "non-deterministically"
+            }
+        )?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " at block {}", block_number)?;
+        }
+        if let Some(handler) = &self.handler {
+            write!(f, " in handler {}", handler)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Indexers whose status endpoint failed for the same reason
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreachableSummary {
+    pub failure: EndpointFailure,
+    pub indexers: Vec<String>,
+}
+
+/// An indexer that is not synced yet, weighted by its allocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocatorBehind {
+    pub indexer_id: String,
+    pub allocated_tokens: f64,
+    pub progress: f32,
+}
+
+/// Aggregated indexing statuses across the queried indexers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusSummary {
+    pub num_currently_allocated_indexer_apis: usize,
+    pub num_indexing_indexers: usize,
+    pub num_synced_indexers: usize,
+    /// Indexers whose new deployment has reached the current deployment on every chain
+    pub num_caught_up_indexers: usize,
+    pub average_progress: f32,
+    pub chains: Vec<ChainSummary>,
+    /// Fatal errors grouped by block, handler and message, most common first
+    pub fatal_errors: Vec<FatalErrorSummary>,
+    pub num_indexers_with_non_fatal_errors: usize,
+    /// Progress weighted by the self stake of the indexing indexers
+    pub stake_weighted_progress: f32,
+    /// Percentage of the GRT allocated by the queried indexers that is synced on the new deployment
+    pub synced_allocation_share: f32,
+    /// Largest allocators not synced on the new deployment, largest first
+    pub largest_allocators_behind: Vec<AllocatorBehind>,
+    /// Indexers with unusable or unreachable status endpoints, by failure
+    pub unreachable_indexers: Vec<UnreachableSummary>,
+}
+
+/// Indexing statuses of a deployment as rendered to stdout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub deployment: String,
+    pub current_deployment: Option<String>,
+    pub indexers: Vec<IndexerStatusRow>,
+    pub summary: StatusSummary,
+}
+
+const HEADERS: [&str; 14] = [
+    "indexer_id",
+    "url",
+    "health",
+    "synced",
+    "network",
+    "latest_block",
+    "chain_head_block",
+    "progress",
+    "current_block",
+    "blocks_behind_current",
+    "fatal_error",
+    "non_fatal_errors",
+    "staked_tokens",
+    "allocated_tokens",
+];
+
+impl StatusSummary {
+    fn fields(&self) -> Vec<(String, String)> {
+        vec![
+            (
+                "num_currently_allocated_indexer_apis",
+                self.num_currently_allocated_indexer_apis.to_string(),
+            ),
+            (
+                "num_indexing_indexers",
+                self.num_indexing_indexers.to_string(),
+            ),
+            ("num_synced_indexers", self.num_synced_indexers.to_string()),
+            (
+                "num_caught_up_indexers",
+                self.num_caught_up_indexers.to_string(),
+            ),
+            ("average_progress", format!("{:.2}%", self.average_progress)),
+            (
+                "num_indexers_with_non_fatal_errors",
+                self.num_indexers_with_non_fatal_errors.to_string(),
+            ),
+            (
+                "stake_weighted_progress",
+                format!("{:.2}%", self.stake_weighted_progress),
+            ),
+            (
+                "synced_allocation_share",
+                format!("{:.2}%", self.synced_allocation_share),
+            ),
+        ]
+        .into_iter()
+        .map(|(metric, value)| (metric.to_string(), value))
+        .chain(self.chains.iter().flat_map(|chain| {
+            vec![
+                (
+                    format!("{}.num_indexing_indexers", chain.network),
+                    chain.num_indexing_indexers.to_string(),
+                ),
+                (
+                    format!("{}.latest_synced_block", chain.network),
+                    chain.latest_synced_block.clone(),
+                ),
+                (
+                    format!("{}.average_progress", chain.network),
+                    format!("{:.2}%", chain.average_progress),
+                ),
+            ]
+        }))
+        .chain(
+            self.fatal_errors
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (format!("fatal_errors.{}", i), e.to_string())),
+        )
+        .chain(
+            self.largest_allocators_behind
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    (
+                        format!("largest_allocators_behind.{}", i),
+                        format!(
+                            "{} ({:.0} GRT allocated, {:.2}%)",
+                            a.indexer_id, a.allocated_tokens, a.progress
+                        ),
+                    )
+                }),
+        )
+        .chain(self.unreachable_indexers.iter().map(|unreachable| {
+            (
+                format!("unreachable_indexers.{}", unreachable.failure),
+                unreachable.indexers.join(" "),
+            )
+        }))
+        .collect()
+    }
+}
+
+impl StatusReport {
+    pub fn render(&self, format: &OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            OutputFormat::Csv => self.to_csv(),
+            OutputFormat::Table => self.to_table(),
+        }
+    }
+
+    /// Summary in a single human readable message for notifications
+    pub fn summary_message(&self) -> String {
+        let chains = self
+            .summary
+            .chains
+            .iter()
+            .map(|chain| {
+                format!(
+                    "{}: {:.2}% (latest synced block {})",
+                    chain.network, chain.average_progress, chain.latest_synced_block
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("; ");
+        let fatal_errors = self
+            .summary
+            .fatal_errors
+            .iter()
+            .map(|e| format!(" {}.", e))
+            .collect::<String>();
+        let unreachable = if self.summary.unreachable_indexers.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " Unreachable indexers: {}.",
+                self.summary
+                    .unreachable_indexers
+                    .iter()
+                    .map(|u| format!("{} {}", u.indexers.len(), u.failure))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        };
+        format!(
+            "Indexing status of {}: {}/{} indexers synced, {} indexing, {} caught up with the current deployment, average progress {:.2}%, stake-weighted progress {:.2}%, {:.2}% of allocated GRT synced. {}.{}{}",
+            self.deployment,
+            self.summary.num_synced_indexers,
+            self.summary.num_currently_allocated_indexer_apis,
+            self.summary.num_indexing_indexers,
+            self.summary.num_caught_up_indexers,
+            self.summary.average_progress,
+            self.summary.stake_weighted_progress,
+            self.summary.synced_allocation_share,
+            chains,
+            fatal_errors,
+            unreachable
+        )
+    }
+
+    /// Indexer rows followed by a metric,value section for the summary
+    fn to_csv(&self) -> String {
+        let mut lines = vec![HEADERS.join(",")];
+        lines.extend(self.indexers.iter().map(|row| {
+            row.fields()
+                .iter()
+                .map(|f| csv_field(f))
+                .collect::<Vec<String>>()
+                .join(",")
+        }));
+        lines.push(String::new());
+        lines.push(String::from("metric,value"));
+        lines.extend(
+            self.summary
+                .fields()
+                .iter()
+                .map(|(metric, value)| format!("{},{}", metric, csv_field(value))),
+        );
+        lines.join("\n")
+    }
+
+    fn to_table(&self) -> String {
+        let rows: Vec<Vec<String>> = self.indexers.iter().map(|row| row.fields()).collect();
+        let widths: Vec<usize> = HEADERS
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rows.iter()
+                    .map(|row| row[i].len())
+                    .chain(std::iter::once(header.len()))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let format_row = |fields: Vec<String>| {
+            fields
+                .iter()
+                .zip(&widths)
+                .map(|(field, width)| format!("{:<width$}", field, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        let mut lines = vec![format!("Deployment: {}", self.deployment)];
+        if let Some(current) = &self.current_deployment {
+            lines.push(format!("Current deployment: {}", current));
+        }
+        lines.push(String::new());
+        lines.push(format_row(HEADERS.iter().map(|h| h.to_string()).collect()));
+        lines.extend(rows.into_iter().map(format_row));
+        lines.push(String::new());
+        lines.extend(
+            self.summary
+                .fields()
+                .iter()
+                .map(|(metric, value)| format!("{}: {}", metric, value)),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
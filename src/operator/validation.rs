@@ -0,0 +1,183 @@
+use chrono::Utc;
+use tracing::info;
+
+use crate::config::{Config, DeprecationNoticeArg, UpgradeCancelArg, UpgradePresyncArg};
+use crate::query::{query_subgraph_info, StatusQueryError, SubgraphInfo};
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz234567";
+
+/// Check that a deployment hash is a well-formed IPFS CID. CIDv0 is a base58btc
+/// encoded sha2-256 multihash, CIDv1 is prefixed by its multibase encoding
+pub fn is_valid_cid(hash: &str) -> bool {
+    let is_base58 = |s: &str| s.chars().all(|c| BASE58_ALPHABET.contains(c));
+    if hash.starts_with("Qm") {
+        return hash.len() == 46 && is_base58(hash);
+    }
+    if let Some(encoded) = hash.strip_prefix('b') {
+        let values: Option<Vec<usize>> = encoded.chars().map(|c| BASE32_ALPHABET.find(c)).collect();
+        // A version byte, codec, and sha2-256 multihash take at least 58 characters
+        return match values {
+            Some(values) if values.len() >= 58 => ((values[0] << 3) | (values[1] >> 2)) == 1,
+            _ => false,
+        };
+    }
+    if let Some(encoded) = hash.strip_prefix('z') {
+        return encoded.len() >= 48 && is_base58(encoded);
+    }
+    false
+}
+
+/// Check the upgrade intent against the network subgraph before it is gossiped
+pub async fn validate_upgrade_presync(
+    config: &Config,
+    args: &UpgradePresyncArg,
+) -> Result<(), ValidationError> {
+    if !is_valid_cid(&args.new_hash) {
+        return Err(ValidationError::InvalidHash(args.new_hash.clone()));
+    }
+
+    let subgraph_info = query_owned_subgraph(config, &args.subgraph_id).await?;
+    if subgraph_info.current_deployment.as_deref() == Some(args.new_hash.as_str()) {
+        return Err(ValidationError::CurrentDeployment(args.new_hash.clone()));
+    }
+
+    info!(
+        subgraph_id = args.subgraph_id,
+        new_hash = args.new_hash,
+        "Validated upgrade intent"
+    );
+    Ok(())
+}
+
+/// Check the deprecation notice against the network subgraph before it is gossiped
+pub async fn validate_deprecation_notice(
+    config: &Config,
+    args: &DeprecationNoticeArg,
+) -> Result<(), ValidationError> {
+    if let Some(deployment) = &args.deployment {
+        if !is_valid_cid(deployment) {
+            return Err(ValidationError::InvalidHash(deployment.clone()));
+        }
+    }
+    if args.deprecation_date <= Utc::now().timestamp() {
+        return Err(ValidationError::PastDeprecationDate(args.deprecation_date));
+    }
+    query_owned_subgraph(config, &args.subgraph_id).await?;
+
+    info!(
+        subgraph_id = args.subgraph_id,
+        deployment = args.deployment,
+        deprecation_date = args.deprecation_date,
+        "Validated deprecation notice"
+    );
+    Ok(())
+}
+
+/// Check the upgrade cancellation against the network subgraph before it is gossiped
+pub async fn validate_upgrade_cancel(
+    config: &Config,
+    args: &UpgradeCancelArg,
+) -> Result<(), ValidationError> {
+    if !is_valid_cid(&args.new_hash) {
+        return Err(ValidationError::InvalidHash(args.new_hash.clone()));
+    }
+    query_owned_subgraph(config, &args.subgraph_id).await?;
+
+    info!(
+        subgraph_id = args.subgraph_id,
+        new_hash = args.new_hash,
+        "Validated upgrade cancellation"
+    );
+    Ok(())
+}
+
+/// Query the subgraph and check that it is active and owned by the configured
+/// graph account
+async fn query_owned_subgraph(
+    config: &Config,
+    subgraph_id: &str,
+) -> Result<SubgraphInfo, ValidationError> {
+    let subgraph_info =
+        query_subgraph_info(config.graph_stack().network_subgraph(), subgraph_id).await?;
+    let graph_account = &config.graph_stack().graph_account;
+    if !subgraph_info.owner.eq_ignore_ascii_case(graph_account) {
+        return Err(ValidationError::NotOwner {
+            owner: subgraph_info.owner,
+            graph_account: graph_account.clone(),
+        });
+    }
+    if !subgraph_info.active {
+        return Err(ValidationError::Deprecated(subgraph_id.to_string()));
+    }
+    Ok(subgraph_info)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationError {
+    #[error("New hash is not a valid IPFS CID: {0}")]
+    InvalidHash(String),
+    #[error("New hash is already the current deployment of the subgraph: {0}")]
+    CurrentDeployment(String),
+    #[error("Graph account {graph_account} is not the owner of the subgraph (owner: {owner})")]
+    NotOwner {
+        owner: String,
+        graph_account: String,
+    },
+    #[error("Subgraph is deprecated: {0}")]
+    Deprecated(String),
+    #[error("Deprecation date is not in the future: {0}")]
+    PastDeprecationDate(i64),
+    #[error(
+        "No upgrade intent to {new_hash} recorded for subgraph {subgraph_id}, provide its nonce"
+    )]
+    UnknownIntent {
+        subgraph_id: String,
+        new_hash: String,
+    },
+    #[error("Could not query the network subgraph: {0}")]
+    Query(#[from] StatusQueryError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_cid() {
+        let cases = [
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x", true),
+            (
+                "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+                true,
+            ),
+            ("zdj7Wic6KcJAfWz1c9o4M6kq9Lwd5BfbxkVafnrojaaGiSFxM", true),
+            // Wrong length
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9", false),
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9xx", false),
+            ("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oc", false),
+            ("zdj7Wic6KcJAfWz1c9o4M6kq9Lwd5Bfbxk", false),
+            // Characters outside of the base58 alphabet
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH90", false),
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9O", false),
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9I", false),
+            ("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9l", false),
+            ("zdj7Wic6KcJAfWz1c9o4M6kq9Lwd5BfbxkVafnrojaaGiSFx0", false),
+            // Characters outside of the base32 alphabet
+            (
+                "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzd1",
+                false,
+            ),
+            (
+                "bAFYBEIGDYRZT5SFP7UDM7HU76UH7Y26NF3EFUYLQABF3OCLGTQY55FBZDI",
+                false,
+            ),
+            // Unknown multibase prefix
+            ("fVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x", false),
+            ("", false),
+        ];
+        for (hash, valid) in cases {
+            assert_eq!(is_valid_cid(hash), valid, "{}", hash);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::time::{sleep, Instant};
+use tracing::info;
+
+use crate::config::IndexingStatusArg;
+use crate::notifier::Notifier;
+use crate::operator::operation::status_report;
+use crate::operator::summary::{IndexerStatusRow, StatusSummary};
+use crate::operator::OperationError;
+use crate::query::{IndexerUrl, StatusClient};
+use crate::store::{StatusSnapshot, Store};
+
+/// Blocks of an indexer on a chain at the time of a poll
+#[derive(Debug, Clone)]
+struct ProgressSnapshot {
+    latest_block: u64,
+    chain_head_block: u64,
+    time: Instant,
+}
+
+impl ProgressSnapshot {
+    fn new(row: &IndexerStatusRow, time: Instant) -> Self {
+        ProgressSnapshot {
+            latest_block: row.latest_block,
+            chain_head_block: row.chain_head_block,
+            time,
+        }
+    }
+
+    /// Estimate the time to reach the chain head from the indexing and chain
+    /// growth rates between two snapshots
+    fn time_to_chain_head(&self, previous: &ProgressSnapshot) -> Option<Duration> {
+        let elapsed = self.time.duration_since(previous.time).as_secs_f64();
+        if elapsed == 0.0 {
+            return None;
+        }
+        let indexing_rate =
+            self.latest_block.saturating_sub(previous.latest_block) as f64 / elapsed;
+        let chain_rate = self
+            .chain_head_block
+            .saturating_sub(previous.chain_head_block) as f64
+            / elapsed;
+        let remaining = self.chain_head_block.saturating_sub(self.latest_block) as f64;
+        if remaining == 0.0 {
+            return Some(Duration::ZERO);
+        }
+        let closing_rate = indexing_rate - chain_rate;
+        (closing_rate > 0.0).then(|| Duration::from_secs_f64(remaining / closing_rate))
+    }
+}
+
+impl IndexingStatusArg {
+    /// Watch mode is done once every provided threshold is met
+    pub fn threshold_met(&self, summary: &StatusSummary) -> bool {
+        if self.until_synced.is_none() && self.until_progress.is_none() {
+            return false;
+        }
+        self.until_synced
+            .is_none_or(|n| summary.num_synced_indexers >= n)
+            && self
+                .until_progress
+                .is_none_or(|p| summary.average_progress >= p)
+    }
+}
+
+/// Poll the indexing statuses at the interval until the sync threshold is
+/// reached, logging block deltas and estimated time to chain head per indexer
+pub async fn watch_indexing_status(
+    args: &IndexingStatusArg,
+    client: &StatusClient,
+    public_status_apis: Vec<IndexerUrl>,
+    current_deployment: Option<&str>,
+    notifier: &Notifier,
+    store: &Store,
+) -> Result<(), OperationError> {
+    let start = Instant::now();
+    let mut previous: HashMap<(String, String), ProgressSnapshot> = HashMap::new();
+    let mut num_synced_indexers = 0;
+
+    loop {
+        let report =
+            status_report(args, client, public_status_apis.clone(), current_deployment).await;
+        let now = Instant::now();
+        for row in &report.indexers {
+            let key = (row.indexer_id.clone(), row.network.clone());
+            let snapshot = ProgressSnapshot::new(row, now);
+            if let Some(prev) = previous.get(&key) {
+                info!(
+                    indexer = row.indexer_id,
+                    network = row.network,
+                    latest_block = row.latest_block,
+                    blocks_indexed = row.latest_block as i64 - prev.latest_block as i64,
+                    blocks_behind = row.chain_head_block.saturating_sub(row.latest_block),
+                    time_to_chain_head = snapshot
+                        .time_to_chain_head(prev)
+                        .map(|eta| format!("{}s", eta.as_secs()))
+                        .unwrap_or(String::from("N/A")),
+                    "Indexer progress"
+                );
+            }
+            previous.insert(key, snapshot);
+        }
+        store.record_snapshot(StatusSnapshot::new(&args.subgraph_id, &report));
+        if let Some(format) = &args.output {
+            println!("{}", report.render(format));
+        }
+        // Milestone on every newly synced indexer
+        if report.summary.num_synced_indexers > num_synced_indexers {
+            num_synced_indexers = report.summary.num_synced_indexers;
+            notifier
+                .notify(format!(
+                    "{} indexers are synced on {}",
+                    num_synced_indexers, args.new_hash
+                ))
+                .await;
+        }
+
+        if args.threshold_met(&report.summary) {
+            info!(
+                num_synced_indexers = report.summary.num_synced_indexers,
+                average_progress = format!("{}%", report.summary.average_progress),
+                elapsed = format!("{}s", start.elapsed().as_secs()),
+                "Sync threshold reached"
+            );
+            notifier
+                .notify(format!(
+                    "Sync threshold reached. {}",
+                    report.summary_message()
+                ))
+                .await;
+            return Ok(());
+        }
+        match args.timeout {
+            Some(timeout) if start.elapsed() >= timeout => {
+                notifier
+                    .notify(format!(
+                        "Sync threshold not reached within {}s. {}",
+                        timeout.as_secs(),
+                        report.summary_message()
+                    ))
+                    .await;
+                return Err(OperationError::Timeout(timeout));
+            }
+            // Poll a final time at the timeout
+            Some(timeout) => {
+                sleep(args.interval.min(timeout.saturating_sub(start.elapsed()))).await
+            }
+            None => sleep(args.interval).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(latest_block: u64, chain_head_block: u64, time: Instant) -> ProgressSnapshot {
+        ProgressSnapshot {
+            latest_block,
+            chain_head_block,
+            time,
+        }
+    }
+
+    fn summary(num_synced_indexers: usize, average_progress: f32) -> StatusSummary {
+        StatusSummary {
+            num_currently_allocated_indexer_apis: 3,
+            num_indexing_indexers: 3,
+            num_synced_indexers,
+            num_caught_up_indexers: 0,
+            average_progress,
+            chains: vec![],
+            fatal_errors: vec![],
+            num_indexers_with_non_fatal_errors: 0,
+            stake_weighted_progress: average_progress,
+            synced_allocation_share: 0.0,
+            largest_allocators_behind: vec![],
+            unreachable_indexers: vec![],
+        }
+    }
+
+    #[test]
+    fn time_to_chain_head_estimates() {
+        let start = Instant::now();
+        let later = start + Duration::from_secs(10);
+
+        // Zero elapsed time gives no rate
+        assert_eq!(
+            snapshot(150, 210, start).time_to_chain_head(&snapshot(100, 200, start)),
+            None
+        );
+        // Already synced
+        assert_eq!(
+            snapshot(210, 210, later).time_to_chain_head(&snapshot(200, 200, start)),
+            Some(Duration::ZERO)
+        );
+        // Indexing 5 blocks/s while the chain grows 1 block/s, 60 blocks behind
+        assert_eq!(
+            snapshot(150, 210, later).time_to_chain_head(&snapshot(100, 200, start)),
+            Some(Duration::from_secs(15))
+        );
+        // Not catching up: stalled, or indexing as fast as the chain grows
+        assert_eq!(
+            snapshot(100, 210, later).time_to_chain_head(&snapshot(100, 200, start)),
+            None
+        );
+        assert_eq!(
+            snapshot(110, 210, later).time_to_chain_head(&snapshot(100, 200, start)),
+            None
+        );
+    }
+
+    #[test]
+    fn threshold_met_requires_every_provided_threshold() {
+        let args = |until_synced, until_progress| IndexingStatusArg {
+            until_synced,
+            until_progress,
+            ..Default::default()
+        };
+
+        // Without a threshold, watch mode runs until interrupted
+        assert!(!args(None, None).threshold_met(&summary(3, 100.0)));
+        assert!(args(Some(2), None).threshold_met(&summary(2, 10.0)));
+        assert!(!args(Some(2), None).threshold_met(&summary(1, 100.0)));
+        assert!(args(None, Some(95.0)).threshold_met(&summary(0, 95.0)));
+        assert!(!args(None, Some(95.0)).threshold_met(&summary(3, 94.9)));
+        assert!(args(Some(2), Some(95.0)).threshold_met(&summary(2, 99.0)));
+        assert!(!args(Some(2), Some(95.0)).threshold_met(&summary(2, 50.0)));
+        assert!(!args(Some(2), Some(95.0)).threshold_met(&summary(1, 99.0)));
+    }
+}
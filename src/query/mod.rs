@@ -1,14 +1,25 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::num::IntErrorKind;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{debug, trace};
+use tokio::sync::Semaphore;
+use tracing::{debug, trace, warn};
 
-use graphcast_sdk::graphql::QueryError;
+/// Number of allocations per page of the network subgraph query
+pub const ALLOCATIONS_PAGE_SIZE: usize = 1000;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct IndexerUrl {
     pub id: String,
     pub url: String,
+    /// Self stake of the indexer in GRT
+    #[serde(default)]
+    pub staked_tokens: f64,
+    /// GRT allocated by the indexer across the queried allocations
+    #[serde(default)]
+    pub allocated_tokens: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,10 +29,53 @@ pub struct IndexerInfo {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct IndexingStatus {
     pub health: SubgraphHealth,
     pub synced: bool,
     pub chains: Vec<ChainStatus>,
+    pub fatal_error: Option<SubgraphError>,
+    #[serde(default)]
+    pub non_fatal_errors: Vec<SubgraphError>,
+}
+
+/// Error reported by graph-node while indexing a deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubgraphError {
+    pub message: String,
+    pub block: Option<Block>,
+    pub handler: Option<String>,
+    pub deterministic: bool,
+}
+
+impl SubgraphError {
+    pub fn block_number(&self) -> Option<u64> {
+        self.block
+            .as_ref()
+            .and_then(|block| block.parse_number().ok())
+    }
+}
+
+impl std::fmt::Display for SubgraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            if self.deterministic {
+                "Deterministic error"
+            } else {
+                "Non-deterministic error"
+            },
+            self.message
+        )?;
+        if let Some(block_number) = self.block_number() {
+            write!(f, " at block {}", block_number)?;
+        }
+        if let Some(handler) = &self.handler {
+            write!(f, " in handler {}", handler)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -32,51 +86,316 @@ pub enum SubgraphHealth {
     failed,
 }
 
+/// Status of a chain indexed by the deployment. Blocks are queried through the
+/// ChainIndexingStatus interface so that Ethereum, NEAR, Arweave, Cosmos and
+/// Substreams statuses share the same shape
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChainStatus {
+    /// Concrete status type reported by graph-node, e.g. NearIndexingStatus
+    #[serde(rename = "__typename", default)]
+    pub chain_type: Option<String>,
     pub network: String,
-    pub latest_block: Block,
-    pub chain_head_block: Block,
+    /// Not set until the deployment has processed a block
+    pub latest_block: Option<Block>,
+    pub chain_head_block: Option<Block>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub number: String,
-    pub hash: String,
+    pub hash: Option<String>,
+}
+
+/// Status of an allocation on the network subgraph
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+pub enum AllocationStatus {
+    Active,
+    Closed,
+    Finalized,
+    Claimed,
+}
+
+/// Proof of indexing of a deployment at a block as computed by an indexer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerPoi {
+    pub info: IndexerUrl,
+    pub block_hash: String,
+    pub proof_of_indexing: String,
+}
+
+/// Ownership and versioning of a subgraph on the network subgraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubgraphInfo {
+    pub owner: String,
+    pub active: bool,
+    pub current_deployment: Option<String>,
 }
 
 impl IndexerInfo {
-    pub fn latest_block_number(&self) -> i32 {
-        self.status.chains[0]
-            .latest_block
-            .number
-            .parse::<i32>()
-            .unwrap()
+    /// Chains indexed by the deployment, filtered to the given network if provided
+    pub fn chains(&self, network: Option<&str>) -> Vec<&ChainStatus> {
+        self.status
+            .chains
+            .iter()
+            .filter(|chain| network.is_none_or(|n| chain.network == n))
+            .collect()
     }
 
-    pub fn chain_head_block_number(&self) -> i32 {
-        self.status.chains[0]
-            .chain_head_block
-            .number
-            .parse::<i32>()
-            .unwrap()
+    /// Progress of the deployment is limited by its least synced chain
+    pub fn progress(&self, network: Option<&str>) -> Option<f32> {
+        self.chains(network)
+            .iter()
+            .map(|chain| chain.progress())
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+impl IndexingStatus {
+    /// Check that the status has chains with valid block numbers, so the block
+    /// accessors can be used on successfully queried statuses
+    pub fn validate(&self) -> Result<(), StatusQueryError> {
+        if self.chains.is_empty() {
+            return Err(StatusQueryError::EmptyChains);
+        }
+        for block in self
+            .chains
+            .iter()
+            .flat_map(|chain| [&chain.latest_block, &chain.chain_head_block])
+            .flatten()
+        {
+            block.parse_number()?;
+        }
+        Ok(())
+    }
+}
+
+impl ChainStatus {
+    /// Block numbers are validated when the indexing status is queried, missing
+    /// blocks are counted as block 0
+    pub fn latest_block_number(&self) -> u64 {
+        self.latest_block
+            .as_ref()
+            .and_then(|block| block.parse_number().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn chain_head_block_number(&self) -> u64 {
+        self.chain_head_block
+            .as_ref()
+            .and_then(|block| block.parse_number().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn progress(&self) -> f32 {
+        let chain_head = self.chain_head_block_number();
+        if chain_head == 0 {
+            return 0.0;
+        }
+        self.latest_block_number() as f32 / chain_head as f32 * 100.0
+    }
+}
+
+impl Block {
+    pub fn parse_number(&self) -> Result<u64, StatusQueryError> {
+        self.number.parse::<u64>().map_err(|e| match e.kind() {
+            IntErrorKind::PosOverflow => StatusQueryError::BlockNumberOverflow(self.number.clone()),
+            _ => StatusQueryError::MalformedResponse(format!(
+                "Invalid block number {}: {}",
+                self.number, e
+            )),
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatusQueryError {
+    #[error("Request failed: {0}")]
+    Request(reqwest::Error),
+    #[error("Request timed out: {0}")]
+    Timeout(reqwest::Error),
+    #[error("Malformed response: {0}")]
+    MalformedResponse(String),
+    #[error("Subgraph not found on the network subgraph: {0}")]
+    MissingSubgraph(String),
+    #[error("Indexing status has no chains")]
+    EmptyChains,
+    #[error("Block number does not fit in u64: {0}")]
+    BlockNumberOverflow(String),
+    #[error("Query task failed: {0}")]
+    Task(tokio::task::JoinError),
+    #[error("Invalid indexer url: {0}")]
+    InvalidUrl(String),
+    #[error("Status endpoint responded with HTTP status {0}")]
+    HttpStatus(reqwest::StatusCode),
+    #[error("Status endpoint responded with GraphQL errors: {0}")]
+    GraphQl(String),
+}
+
+/// Reason the public status endpoint of an indexer could not be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointFailure {
+    InvalidUrl,
+    Dns,
+    Tls,
+    Connection,
+    Timeout,
+    HttpStatus,
+    GraphQl,
+}
+
+impl std::fmt::Display for EndpointFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EndpointFailure::InvalidUrl => "invalid_url",
+            EndpointFailure::Dns => "dns",
+            EndpointFailure::Tls => "tls",
+            EndpointFailure::Connection => "connection",
+            EndpointFailure::Timeout => "timeout",
+            EndpointFailure::HttpStatus => "http_status",
+            EndpointFailure::GraphQl => "graphql",
+        };
+        write!(f, "{}", name)
     }
 }
 
-/// Construct indexer url query
-pub fn active_indexer_url(subgraph_id: &str) -> serde_json::Value {
+impl StatusQueryError {
+    /// Timeouts, failed connections and server side errors may succeed on retry
+    pub fn is_transient(&self) -> bool {
+        match self {
+            StatusQueryError::Timeout(_) => true,
+            StatusQueryError::Request(e) => e.is_connect(),
+            StatusQueryError::HttpStatus(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Categorize errors caused by an unusable or unreachable status endpoint, as
+    /// opposed to an indexer reporting an unexpected status
+    pub fn endpoint_failure(&self) -> Option<EndpointFailure> {
+        match self {
+            StatusQueryError::InvalidUrl(_) => Some(EndpointFailure::InvalidUrl),
+            StatusQueryError::HttpStatus(_) => Some(EndpointFailure::HttpStatus),
+            StatusQueryError::GraphQl(_) => Some(EndpointFailure::GraphQl),
+            StatusQueryError::Timeout(_) => Some(EndpointFailure::Timeout),
+            StatusQueryError::Request(e) if e.is_builder() => Some(EndpointFailure::InvalidUrl),
+            StatusQueryError::Request(e) => {
+                // reqwest does not expose the cause of connection failures other
+                // than through the messages of the underlying errors
+                let causes = error_causes(e).to_lowercase();
+                if causes.contains("dns error") || causes.contains("failed to lookup address") {
+                    Some(EndpointFailure::Dns)
+                } else if ["tls", "ssl", "certificate", "handshake"]
+                    .iter()
+                    .any(|cause| causes.contains(cause))
+                {
+                    Some(EndpointFailure::Tls)
+                } else if e.is_connect() || e.is_request() {
+                    Some(EndpointFailure::Connection)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Messages of an error and its sources
+fn error_causes(e: &dyn std::error::Error) -> String {
+    let mut causes = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        causes.push_str(": ");
+        causes.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    causes
+}
+
+/// Join the status path onto the url registered by an indexer. Path prefixes are
+/// kept, query strings and fragments are dropped
+pub fn status_endpoint(url: &str) -> Result<reqwest::Url, StatusQueryError> {
+    let invalid = |reason: &str| StatusQueryError::InvalidUrl(format!("{} ({})", url, reason));
+    let mut endpoint = reqwest::Url::parse(url.trim()).map_err(|e| invalid(&e.to_string()))?;
+    if !matches!(endpoint.scheme(), "http" | "https") {
+        return Err(invalid("scheme must be http or https"));
+    }
+    if endpoint.host_str().unwrap_or_default().is_empty() {
+        return Err(invalid("missing host"));
+    }
+    endpoint.set_query(None);
+    endpoint.set_fragment(None);
+    // Without a trailing slash the last path segment would be replaced
+    if !endpoint.path().ends_with('/') {
+        let path = format!("{}/", endpoint.path());
+        endpoint.set_path(&path);
+    }
+    endpoint.join("status").map_err(|e| invalid(&e.to_string()))
+}
+
+impl From<reqwest::Error> for StatusQueryError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            StatusQueryError::Timeout(e)
+        } else {
+            StatusQueryError::Request(e)
+        }
+    }
+}
+
+/// Construct a page of the indexer url query for allocations on the deployments,
+/// ordered by allocation id after last_id
+pub fn allocated_indexer_urls_query(
+    deployments: &[String],
+    statuses: &[AllocationStatus],
+    last_id: &str,
+) -> serde_json::Value {
+    json!({
+        "query": r#"query allocations($deployments: [String!]!, $statuses: [AllocationStatus!]!, $lastId: String!, $first: Int!){
+            allocations(
+              first: $first,
+              orderBy: id,
+              orderDirection: asc,
+              where: {
+                id_gt: $lastId,
+                status_in: $statuses,
+                subgraphDeployment_: { ipfsHash_in: $deployments }
+              }
+            ) {
+              id
+              allocatedTokens
+              indexer {
+                id
+                url
+                stakedTokens
+              }
+            }
+        }"#,
+        "variables": {
+            "deployments": deployments,
+            "statuses": statuses,
+            "lastId": last_id.to_string(),
+            "first": ALLOCATIONS_PAGE_SIZE,
+        },
+    })
+}
+
+/// Construct subgraph owner and current version query
+pub fn subgraph_info_query(subgraph_id: &str) -> serde_json::Value {
     json!({
         "query": r#"query subgraph($id: String!){
             subgraph(id: $id) {
+            owner {
+              id
+            }
+            active
             currentVersion{
               subgraphDeployment{
-                indexerAllocations{
-                  indexer {
-                    id
-                    url
-                  }
-                }
+                ipfsHash
               }
             }
           }
@@ -95,12 +414,13 @@ pub fn status_query(deployment: &str) -> serde_json::Value {
                 subgraph
                 health
                 synced
+                fatalError { message block { number hash } handler deterministic }
+                nonFatalErrors { message block { number hash } handler deterministic }
                 chains {
+                    __typename
                     network
-                    ... on EthereumIndexingStatus {
-                        latestBlock { number hash }
-                        chainHeadBlock { number hash }
-                    }
+                    latestBlock { number hash }
+                    chainHeadBlock { number hash }
                 }
             }
         }"#,
@@ -112,15 +432,110 @@ pub fn status_query(deployment: &str) -> serde_json::Value {
 
 /// Query the network subgraph to get a list of indexer id to their
 /// public status endpoint registered by the indexers allocating to the
-/// current deployment of the subgraph
+/// deployments, paginating through every allocation with the statuses and
+/// deduplicating indexers allocated multiple times
 pub async fn query_indexer_public_api(
+    network_subgraph_endpoint: &str,
+    deployments: &[String],
+    statuses: &[AllocationStatus],
+) -> Result<Vec<IndexerUrl>, StatusQueryError> {
+    let mut indexer_urls: Vec<IndexerUrl> = vec![];
+    if deployments.is_empty() {
+        return Ok(indexer_urls);
+    }
+    let client = reqwest::Client::new();
+    let mut last_id = String::new();
+
+    loop {
+        // Create GraphQL query string
+        let query = allocated_indexer_urls_query(deployments, statuses, &last_id);
+
+        // Send the GraphQL request
+        let response = client
+            .post(network_subgraph_endpoint)
+            .header("Content-Type", "application/json")
+            .json(&query)
+            .send()
+            .await?;
+
+        // Deserialize the JSON response
+        let data: serde_json::Value = response.json().await?;
+        if let Some(errors) = data.get("errors") {
+            return Err(StatusQueryError::MalformedResponse(errors.to_string()));
+        }
+        let allocations = data["data"]["allocations"].as_array().ok_or_else(|| {
+            StatusQueryError::MalformedResponse(String::from("No allocations in response"))
+        })?;
+        for allocation in allocations {
+            let Some(id) = allocation["indexer"]["id"].as_str() else {
+                continue;
+            };
+            let Some(url) = allocation["indexer"]["url"].as_str() else {
+                continue;
+            };
+            let allocated_tokens = grt(&allocation["allocatedTokens"]);
+            match indexer_urls.iter_mut().find(|i| i.id == id) {
+                Some(indexer_url) => indexer_url.allocated_tokens += allocated_tokens,
+                None => indexer_urls.push(IndexerUrl {
+                    id: id.to_string(),
+                    url: url.to_string(),
+                    staked_tokens: grt(&allocation["indexer"]["stakedTokens"]),
+                    allocated_tokens,
+                }),
+            }
+        }
+
+        match allocations.last().and_then(|a| a["id"].as_str()) {
+            Some(id) if allocations.len() == ALLOCATIONS_PAGE_SIZE => last_id = id.to_string(),
+            _ => break,
+        }
+    }
+    debug!(
+        indexer_urls = tracing::field::debug(&indexer_urls),
+        "Queried Indexer URLs"
+    );
+    Ok(indexer_urls)
+}
+
+/// Convert a BigInt amount of wei returned by the network subgraph to GRT
+fn grt(value: &serde_json::Value) -> f64 {
+    value
+        .as_str()
+        .and_then(|wei| wei.parse::<f64>().ok())
+        .unwrap_or_default()
+        / 1e18
+}
+
+/// Construct public proof of indexing query, the proof of indexing query
+/// allowed on the public status endpoint of indexers
+pub fn poi_query(deployment: &str, block_number: u64) -> serde_json::Value {
+    json!({
+        "query": r#"query publicProofsOfIndexing($requests: [PublicProofOfIndexingRequest!]!) {
+            publicProofsOfIndexing(requests: $requests) {
+                deployment
+                proofOfIndexing
+                block {
+                    number
+                    hash
+                }
+            }
+        }"#,
+        "variables": {
+            "requests": [{
+                "deployment": deployment.to_string(),
+                "blockNumber": block_number.to_string(),
+            }],
+        },
+    })
+}
+
+/// Query the network subgraph for the owner, deprecation and current deployment of a subgraph
+pub async fn query_subgraph_info(
     network_subgraph_endpoint: &str,
     subgraph_id: &str,
-) -> Result<Vec<IndexerUrl>, QueryError> {
-    // Create GraphQL query string
-    let query = active_indexer_url(subgraph_id);
+) -> Result<SubgraphInfo, StatusQueryError> {
+    let query = subgraph_info_query(subgraph_id);
 
-    // Send the GraphQL request
     let response = reqwest::Client::new()
         .post(network_subgraph_endpoint)
         .header("Content-Type", "application/json")
@@ -128,47 +543,150 @@ pub async fn query_indexer_public_api(
         .send()
         .await?;
 
-    // Deserialize the JSON response
     let data: serde_json::Value = response.json().await?;
-    let indexer_urls = data["data"]["subgraph"]["currentVersion"]["subgraphDeployment"]
-        ["indexerAllocations"]
-        .as_array()
-        .unwrap_or_else(|| panic!("Could not get indexer public status APIs"))
-        .iter()
-        .filter_map(|v| serde_json::from_value::<IndexerUrl>(v["indexer"].clone()).ok())
-        .collect::<Vec<IndexerUrl>>();
+    if let Some(errors) = data.get("errors") {
+        return Err(StatusQueryError::MalformedResponse(errors.to_string()));
+    }
+    let subgraph = &data["data"]["subgraph"];
+    if subgraph.is_null() {
+        return Err(StatusQueryError::MissingSubgraph(subgraph_id.to_string()));
+    }
+    let subgraph_info = SubgraphInfo {
+        owner: subgraph["owner"]["id"]
+            .as_str()
+            .ok_or_else(|| {
+                StatusQueryError::MalformedResponse(format!(
+                    "No owner for subgraph {}",
+                    subgraph_id
+                ))
+            })?
+            .to_string(),
+        active: subgraph["active"].as_bool().unwrap_or(true),
+        current_deployment: subgraph["currentVersion"]["subgraphDeployment"]["ipfsHash"]
+            .as_str()
+            .map(String::from),
+    };
     debug!(
-        indexer_urls = tracing::field::debug(&indexer_urls),
-        "Queried Indexer URLs"
+        subgraph_info = tracing::field::debug(&subgraph_info),
+        "Queried subgraph info"
     );
-    Ok(indexer_urls)
+    Ok(subgraph_info)
+}
+
+/// Base delay between retries of a status endpoint query, doubled on every attempt
+const STATUS_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// HTTP client shared by the queries to the public status endpoints of indexers.
+/// Connections are pooled across queries and the number of queries in flight is
+/// capped, so that deployments with hundreds of allocations do not exhaust file
+/// descriptors
+#[derive(Debug, Clone)]
+pub struct StatusClient {
+    client: reqwest::Client,
+    permits: Arc<Semaphore>,
+    max_retry: usize,
+}
+
+impl StatusClient {
+    pub fn new(
+        max_concurrent_queries: usize,
+        connect_timeout: Duration,
+        request_timeout: Duration,
+        max_retry: usize,
+    ) -> Result<Self, StatusQueryError> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(request_timeout)
+            .build()?;
+        Ok(StatusClient {
+            client,
+            permits: Arc::new(Semaphore::new(max_concurrent_queries.max(1))),
+            max_retry,
+        })
+    }
+
+    /// Send a GraphQL query to the public status endpoint of an indexer, retrying
+    /// on transient errors
+    async fn query(
+        &self,
+        indexer_url: &IndexerUrl,
+        query: &serde_json::Value,
+    ) -> Result<serde_json::Value, StatusQueryError> {
+        let mut attempt = 0;
+        loop {
+            match self.query_once(indexer_url, query).await {
+                Err(e) if e.is_transient() && attempt < self.max_retry => {
+                    let delay = STATUS_RETRY_DELAY * 2_u32.saturating_pow(attempt as u32);
+                    warn!(
+                        indexer = indexer_url.id,
+                        attempt,
+                        delay = format!("{}ms", delay.as_millis()),
+                        err = e.to_string(),
+                        "Status endpoint query failed, retrying"
+                    );
+                    attempt += 1;
+                    // The permit is released while waiting so other endpoints can be queried
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn query_once(
+        &self,
+        indexer_url: &IndexerUrl,
+        query: &serde_json::Value,
+    ) -> Result<serde_json::Value, StatusQueryError> {
+        let status_endpoint = status_endpoint(&indexer_url.url)?;
+        // The semaphore is never closed, so acquiring a permit cannot fail
+        let _permit = self.permits.acquire().await;
+        // Send the GraphQL request
+        let response = self
+            .client
+            .post(status_endpoint)
+            .header("Content-Type", "application/json")
+            .json(query)
+            .send()
+            .await?;
+        trace!("response: {:#?}", &response);
+        if !response.status().is_success() {
+            return Err(StatusQueryError::HttpStatus(response.status()));
+        }
+
+        // Deserialize the JSON response
+        let data: serde_json::Value = response.json().await?;
+        // Errors come without data when graph-node cannot execute the query at all
+        if data["data"].is_null() {
+            if let Some(errors) = data["errors"].as_array() {
+                return Err(StatusQueryError::GraphQl(
+                    errors
+                        .iter()
+                        .map(|e| e["message"].as_str().unwrap_or_default())
+                        .collect::<Vec<&str>>()
+                        .join("; "),
+                ));
+            }
+        }
+        Ok(data)
+    }
 }
 
 /// Query the network subgraph to get a hashmap of indexer id to their
 /// public status endpoint registered by the indexers allocating to the
 /// current deployment of the subgraph
 pub async fn query_indexing_status(
+    client: StatusClient,
     indexer_url: IndexerUrl,
     deployment: String,
-) -> Result<IndexerInfo, QueryError> {
+) -> Result<IndexerInfo, StatusQueryError> {
     // Create GraphQL query string
     let query = status_query(&deployment);
-    let status_endpoint = indexer_url.url.to_string() + "status";
-    // Send the GraphQL request
-    let response = reqwest::Client::new()
-        .post(status_endpoint)
-        .header("Content-Type", "application/json")
-        .json(&query)
-        .timeout(Duration::from_secs(10))
-        .send()
-        .await?;
-    trace!("response: {:#?}", &response);
-
-    // Deserialize the JSON response
-    let data: serde_json::Value = response.json().await?;
+    let data = client.query(&indexer_url, &query).await?;
     let indexing_status =
         serde_json::from_value::<IndexingStatus>(data["data"]["indexingStatuses"][0].clone())
-            .map_err(|e| QueryError::Other(e.into()))?;
+            .map_err(|e| StatusQueryError::MalformedResponse(e.to_string()))?;
+    indexing_status.validate()?;
 
     debug!(
         indexer = tracing::field::debug(&indexer_url),
@@ -181,17 +699,86 @@ pub async fn query_indexing_status(
     })
 }
 
+/// Query the proof of indexing of a deployment at a block from the public status
+/// endpoint of an indexer
+pub async fn query_proof_of_indexing(
+    client: StatusClient,
+    indexer_url: IndexerUrl,
+    deployment: String,
+    block_number: u64,
+) -> Result<IndexerPoi, StatusQueryError> {
+    let data = client
+        .query(&indexer_url, &poi_query(&deployment, block_number))
+        .await?;
+    let result = &data["data"]["publicProofsOfIndexing"][0];
+    let (Some(proof_of_indexing), Some(block_hash)) = (
+        result["proofOfIndexing"].as_str(),
+        result["block"]["hash"].as_str(),
+    ) else {
+        return Err(StatusQueryError::MalformedResponse(format!(
+            "No proof of indexing for {} at block {}",
+            deployment, block_number
+        )));
+    };
+    let proof_of_indexing = proof_of_indexing.to_string();
+    let block_hash = block_hash.to_string();
+
+    debug!(
+        indexer = tracing::field::debug(&indexer_url),
+        block_number, block_hash, proof_of_indexing, "Queried proof of indexing"
+    );
+    Ok(IndexerPoi {
+        info: indexer_url,
+        block_hash,
+        proof_of_indexing,
+    })
+}
+
+/// Query the proofs of indexing of a deployment at a block from indexer_urls
+pub async fn query_proofs_of_indexing(
+    client: &StatusClient,
+    indexer_urls: Vec<IndexerUrl>,
+    deployment: &str,
+    block_number: u64,
+) -> Vec<Result<IndexerPoi, StatusQueryError>> {
+    let handles: Vec<tokio::task::JoinHandle<Result<IndexerPoi, StatusQueryError>>> = indexer_urls
+        .into_iter()
+        .map(|indexer_url| {
+            tokio::spawn(query_proof_of_indexing(
+                client.clone(),
+                indexer_url,
+                deployment.to_string(),
+                block_number,
+            ))
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(
+            handle
+                .await
+                .unwrap_or_else(|e| Err(StatusQueryError::Task(e))),
+        );
+    }
+    results
+}
+
 /// Query indexing_status of a deployment indexer_urls
 pub async fn query_indexing_statuses(
+    client: &StatusClient,
     indexer_urls: Vec<IndexerUrl>,
     deployment: &str,
-) -> Vec<std::result::Result<IndexerInfo, QueryError>> {
-    // Loop through indexer_url to query indexing_status
+) -> Vec<std::result::Result<IndexerInfo, StatusQueryError>> {
+    // Loop through indexer_url to query indexing_status, the client bounds the
+    // number of queries in flight
     let indexing_statuses: Vec<_> = indexer_urls
         .iter()
-        .map(|indexer_url| query_indexing_status(indexer_url.clone(), deployment.to_string()))
+        .map(|indexer_url| {
+            query_indexing_status(client.clone(), indexer_url.clone(), deployment.to_string())
+        })
         .collect();
-    let mut handles: Vec<tokio::task::JoinHandle<Result<IndexerInfo, QueryError>>> =
+    let mut handles: Vec<tokio::task::JoinHandle<Result<IndexerInfo, StatusQueryError>>> =
         Vec::with_capacity(indexing_statuses.len());
 
     for fut in indexing_statuses {
@@ -200,11 +787,15 @@ pub async fn query_indexing_statuses(
 
     let mut results = Vec::with_capacity(handles.len());
     for handle in handles {
-        results.push(handle.await.unwrap());
+        results.push(
+            handle
+                .await
+                .unwrap_or_else(|e| Err(StatusQueryError::Task(e))),
+        );
     }
     let results_len = results.len();
 
-    let err_results: Vec<&QueryError> = results
+    let err_results: Vec<&StatusQueryError> = results
         .iter()
         .filter_map(|result| result.as_ref().err())
         .collect();
@@ -217,3 +808,116 @@ pub async fn query_indexing_statuses(
     trace!(err_results = tracing::field::debug(&err_results), "Errors");
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::stub_server_sequence;
+
+    fn allocation(id: String, indexer: &str, allocated_grt: u64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "allocatedTokens": format!("{}000000000000000000", allocated_grt),
+            "indexer": {
+                "id": indexer,
+                "url": format!("https://{}.xyz", indexer),
+                "stakedTokens": "1000000000000000000000",
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn indexer_urls_are_paginated_and_deduplicated() {
+        let first_page: Vec<serde_json::Value> = (0..ALLOCATIONS_PAGE_SIZE)
+            .map(|i| allocation(format!("0x{:04}", i), ["0xa", "0xb"][i % 2], 1))
+            .collect();
+        let second_page = vec![
+            allocation(String::from("0x1000"), "0xa", 2),
+            allocation(String::from("0x1001"), "0xc", 3),
+        ];
+        let (url, server) = stub_server_sequence(
+            [first_page, second_page]
+                .into_iter()
+                .map(|page| (200, json!({ "data": { "allocations": page } }).to_string()))
+                .collect(),
+        )
+        .await;
+
+        let indexer_urls = query_indexer_public_api(
+            &url,
+            &[String::from(
+                "QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x",
+            )],
+            &[AllocationStatus::Active],
+        )
+        .await
+        .unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].body["variables"]["lastId"], "");
+        assert_eq!(requests[1].body["variables"]["lastId"], "0x0999");
+        let tokens: Vec<(&str, &str, f64, f64)> = indexer_urls
+            .iter()
+            .map(|i| {
+                (
+                    i.id.as_str(),
+                    i.url.as_str(),
+                    i.staked_tokens,
+                    i.allocated_tokens,
+                )
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                ("0xa", "https://0xa.xyz", 1000.0, 502.0),
+                ("0xb", "https://0xb.xyz", 1000.0, 500.0),
+                ("0xc", "https://0xc.xyz", 1000.0, 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_endpoint_url_edge_cases() {
+        let cases = [
+            ("https://indexer.xyz", "https://indexer.xyz/status"),
+            ("https://indexer.xyz/", "https://indexer.xyz/status"),
+            (
+                "https://indexer.xyz/graph",
+                "https://indexer.xyz/graph/status",
+            ),
+            (
+                "https://indexer.xyz/graph/",
+                "https://indexer.xyz/graph/status",
+            ),
+            (
+                "https://indexer.xyz/graph?api-key=1#top",
+                "https://indexer.xyz/graph/status",
+            ),
+            (
+                "https://indexer.xyz/?api-key=1",
+                "https://indexer.xyz/status",
+            ),
+            ("http://10.0.0.1:7600", "http://10.0.0.1:7600/status"),
+            ("  https://indexer.xyz/\n", "https://indexer.xyz/status"),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(status_endpoint(url).unwrap().as_str(), expected, "{url}");
+        }
+
+        for url in [
+            "ftp://indexer.xyz",
+            "file:///status",
+            "http://",
+            "http://:7600",
+            "indexer.xyz",
+            "",
+            "   ",
+        ] {
+            assert!(
+                matches!(status_endpoint(url), Err(StatusQueryError::InvalidUrl(_))),
+                "{url}"
+            );
+        }
+    }
+}
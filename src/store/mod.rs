@@ -0,0 +1,352 @@
+use chrono::{TimeZone, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::{Config, OutputFormat};
+use crate::operator::summary::{csv_field, StatusReport, StatusSummary};
+
+/// Message sent to Graphcast, as recorded for auditing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentMessage {
+    pub msg_id: String,
+    pub message_type: String,
+    pub identifier: String,
+    pub subgraph_id: String,
+    pub new_hash: String,
+    pub nonce: i64,
+    /// Unix timestamp of sending the message
+    pub time: i64,
+    pub sender: String,
+    pub graph_account: String,
+}
+
+/// Summary of an indexing-status run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    /// Unix timestamp of the status queries
+    pub time: i64,
+    pub subgraph_id: String,
+    pub deployment: String,
+    pub current_deployment: Option<String>,
+    pub summary: StatusSummary,
+}
+
+impl StatusSnapshot {
+    pub fn new(subgraph_id: &str, report: &StatusReport) -> Self {
+        StatusSnapshot {
+            time: Utc::now().timestamp(),
+            subgraph_id: subgraph_id.to_string(),
+            deployment: report.deployment.clone(),
+            current_deployment: report.current_deployment.clone(),
+            summary: report.summary.clone(),
+        }
+    }
+}
+
+/// Everything recorded by the CLI
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct State {
+    pub sent_messages: Vec<SentMessage>,
+    pub status_snapshots: Vec<StatusSnapshot>,
+}
+
+/// Contents of the state file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StateFile {
+    sent_messages: Vec<SentMessage>,
+}
+
+/// Local JSON file recording sent messages, with the indexing status snapshots
+/// appended to a JSON lines file next to it
+#[derive(Debug, Clone)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    pub fn new(path: PathBuf) -> Self {
+        Store { path }
+    }
+
+    /// Use the configured state file, defaulting to graphcast-cli/state.json
+    /// under the XDG data directory
+    pub fn from_config(config: &Config) -> Self {
+        let path = match &config.state_file {
+            Some(path) => PathBuf::from(path),
+            None => std::env::var_os("XDG_DATA_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share"))
+                })
+                .unwrap_or_default()
+                .join("graphcast-cli")
+                .join("state.json"),
+        };
+        Store::new(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn snapshots_path(&self) -> PathBuf {
+        self.path.with_extension("snapshots.jsonl")
+    }
+
+    /// Read the state, which is empty until something is recorded
+    pub fn load(&self) -> Result<State, StoreError> {
+        Ok(State {
+            sent_messages: self.load_file()?.sent_messages,
+            status_snapshots: self.load_snapshots()?,
+        })
+    }
+
+    fn load_file(&self) -> Result<StateFile, StoreError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StateFile::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_snapshots(&self) -> Result<Vec<StatusSnapshot>, StoreError> {
+        let contents = match std::fs::read_to_string(self.snapshots_path()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        // A line cut short by an interrupted append is skipped
+        Ok(contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    warn!(err = e.to_string(), "Skipping invalid status snapshot");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    pub fn record_message(&self, message: SentMessage) {
+        self.record(|| self.update(|state| state.sent_messages.push(message)));
+    }
+
+    pub fn record_snapshot(&self, snapshot: StatusSnapshot) {
+        self.record(|| self.append_snapshot(&snapshot));
+    }
+
+    /// Failures are logged and never interrupt the operation being recorded
+    fn record(&self, write: impl FnOnce() -> Result<(), StoreError>) {
+        match write() {
+            Ok(()) => debug!(path = self.path.display().to_string(), "Updated state file"),
+            Err(e) => warn!(
+                path = self.path.display().to_string(),
+                err = e.to_string(),
+                "Failed to update state file"
+            ),
+        }
+    }
+
+    /// Hold an exclusive advisory lock, released when the returned file is
+    /// dropped, so that concurrent commands do not overwrite each other
+    fn lock(&self) -> Result<File, StoreError> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+        lock.lock_exclusive()?;
+        Ok(lock)
+    }
+
+    fn update(&self, update: impl FnOnce(&mut StateFile)) -> Result<(), StoreError> {
+        let _lock = self.lock()?;
+        let mut state = self.load_file()?;
+        update(&mut state);
+        // Write to a temporary file first so that an interrupted write does not
+        // corrupt the state. Writers hold the lock, so the name can be fixed
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&state)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Snapshots are appended so that watch mode does not rewrite the history
+    /// on every poll
+    fn append_snapshot(&self, snapshot: &StatusSnapshot) -> Result<(), StoreError> {
+        let _lock = self.lock()?;
+        let mut line = serde_json::to_string(snapshot)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.snapshots_path())?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// The sent message with the status snapshots of its new hash taken since
+pub fn show_message(state: &State, msg_id: &str) -> Result<serde_json::Value, StoreError> {
+    let message = state
+        .sent_messages
+        .iter()
+        .find(|m| m.msg_id == msg_id)
+        .ok_or_else(|| StoreError::NotFound(msg_id.to_string()))?;
+    let mut snapshots: Vec<&StatusSnapshot> = state
+        .status_snapshots
+        .iter()
+        .filter(|s| s.deployment == message.new_hash && s.time >= message.time)
+        .collect();
+    snapshots.sort_by_key(|s| s.time);
+    Ok(serde_json::json!({
+        "message": message,
+        "status_snapshots": snapshots,
+    }))
+}
+
+/// Sent messages and status snapshots in chronological order
+pub fn render_history(state: &State, format: &OutputFormat) -> String {
+    let mut entries: Vec<(i64, &str, &str, &str, String)> = state
+        .sent_messages
+        .iter()
+        .map(|m| {
+            (
+                m.time,
+                m.message_type.as_str(),
+                m.subgraph_id.as_str(),
+                m.new_hash.as_str(),
+                format!("msg_id {} sent by {}", m.msg_id, m.sender),
+            )
+        })
+        .chain(state.status_snapshots.iter().map(|s| {
+            (
+                s.time,
+                "IndexingStatus",
+                s.subgraph_id.as_str(),
+                s.deployment.as_str(),
+                format!(
+                    "{}/{} indexers synced, average progress {:.2}%",
+                    s.summary.num_synced_indexers,
+                    s.summary.num_currently_allocated_indexer_apis,
+                    s.summary.average_progress
+                ),
+            )
+        }))
+        .collect();
+    entries.sort_by_key(|entry| entry.0);
+
+    match format {
+        OutputFormat::Json => {
+            let mut state = state.clone();
+            state.sent_messages.sort_by_key(|m| m.time);
+            state.status_snapshots.sort_by_key(|s| s.time);
+            serde_json::to_string_pretty(&state).unwrap_or_default()
+        }
+        OutputFormat::Csv => {
+            std::iter::once(String::from("time,kind,subgraph_id,deployment,details"))
+                .chain(
+                    entries
+                        .iter()
+                        .map(|(time, kind, subgraph_id, deployment, details)| {
+                            format!(
+                                "{},{},{},{},{}",
+                                format_time(*time),
+                                kind,
+                                csv_field(subgraph_id),
+                                csv_field(deployment),
+                                csv_field(details)
+                            )
+                        }),
+                )
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+        OutputFormat::Table => entries
+            .iter()
+            .map(|(time, kind, subgraph_id, deployment, details)| {
+                format!(
+                    "{}  {:<22}  {}  {}  {}",
+                    format_time(*time),
+                    kind,
+                    subgraph_id,
+                    deployment,
+                    details
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+fn format_time(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.to_rfc3339())
+        .unwrap_or(timestamp.to_string())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("Could not access the state file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid state file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No sent message with id {0}")]
+    NotFound(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent_message(msg_id: String) -> SentMessage {
+        SentMessage {
+            msg_id,
+            message_type: String::from("UpgradeIntentMessage"),
+            identifier: String::from("QmacQnSgia4iDPWHpeY6aWxesRFdb8o5DKZUx96zZqEWrB"),
+            subgraph_id: String::from("CnJMdCkW3pr619gsJVtUPAWxspALPdCMw6o7obzYBNp3"),
+            new_hash: String::from("QmVVfLWowm1xkqc41vcygKNwFUvpsDSMbHdHghxmDVmH9x"),
+            nonce: 1691078400,
+            time: 1691078400,
+            sender: String::from("0xe9a1cabd57700b17945fd81feefba82340d9568f"),
+            graph_account: String::from("0xe9a1cabd57700b17945fd81feefba82340d9568f"),
+        }
+    }
+
+    #[test]
+    fn concurrent_records_are_kept() {
+        let dir = std::env::temp_dir().join(format!("graphcast-cli-store-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = Store::new(dir.join("state.json"));
+
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        store.record_message(sent_message(format!("{}-{}", writer, i)));
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let state = store.load().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(state.sent_messages.len(), 40);
+    }
+}
@@ -0,0 +1,77 @@
+//! Local HTTP server answering requests with canned responses, for tests of
+//! the HTTP clients
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Request received by the stub server
+pub struct StubRequest {
+    pub path: String,
+    pub head: String,
+    pub body: serde_json::Value,
+}
+
+/// Serve a single HTTP request with the given status and body on a local port
+pub async fn stub_server(status: u16, body: &'static str) -> (String, JoinHandle<StubRequest>) {
+    let (url, handle) = stub_server_sequence(vec![(status, body.to_string())]).await;
+    let handle = tokio::spawn(async move { handle.await.unwrap().remove(0) });
+    (url, handle)
+}
+
+/// Serve one request per response, in order, closing the connection after each
+pub async fn stub_server_sequence(
+    responses: Vec<(u16, String)>,
+) -> (String, JoinHandle<Vec<StubRequest>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let mut requests = vec![];
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            requests.push(read_request(&mut stream).await);
+            let response = format!(
+                "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+async fn read_request(stream: &mut TcpStream) -> StubRequest {
+    let mut buf = vec![];
+    let mut chunk = [0u8; 1024];
+    let (head, body) = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf).to_string();
+        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            continue;
+        };
+        let content_length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or_default();
+        if body.len() >= content_length || n == 0 {
+            break (head.to_string(), body.to_string());
+        }
+    };
+    StubRequest {
+        path: head
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string(),
+        head,
+        body: serde_json::from_str(&body).unwrap_or_default(),
+    }
+}